@@ -5,6 +5,7 @@ pub mod mutation;
 pub mod order;
 pub mod params;
 pub mod prefer;
+pub mod range;
 pub mod rpc;
 pub mod schema;
 pub mod select;
@@ -17,8 +18,14 @@ pub use mutation::{
     UpdateParams,
 };
 pub use order::{Direction, Nulls, OrderTerm};
-pub use params::ParsedParams;
-pub use prefer::{Count, Missing, Plurality, PreferOptions, Resolution, ReturnRepresentation};
-pub use rpc::RpcParams;
-pub use schema::{Cardinality, Column, Junction, Relationship, Table};
-pub use select::{ItemHint, ItemType, SelectItem};
+pub use params::{EmbedParams, ParsedParams};
+pub use prefer::{
+    Count, Missing, ParamsMode, Plurality, PreferOptions, Resolution, ReturnRepresentation,
+};
+pub use range::{ContentRange, RowRange};
+pub use rpc::{RawMediaType, RpcBody, RpcParams};
+pub use schema::{
+    Cardinality, Column, Function, FunctionParam, FunctionReturn, Junction, Relationship,
+    ReturnKind, Table, Volatility,
+};
+pub use select::{Aggregate, AggregateFunction, ItemHint, ItemType, JoinType, SelectItem};
@@ -1,6 +1,6 @@
 use super::{LogicCondition, OrderTerm, ParsedParams, PreferOptions, RpcParams, SelectItem};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 /// Represents all supported PostgREST operations (GET, POST, PATCH, DELETE, PUT, RPC).
 ///
@@ -79,7 +79,7 @@ impl ResolvedTable {
 
     /// Returns the SQL-quoted qualified name: `"schema"."table"`
     pub fn qualified_name(&self) -> String {
-        format!("\"{}\".\"{}\"", self.schema, self.name)
+        crate::sql::fragment::QualifiedIdent(&self.schema, &self.name).to_string()
     }
 }
 
@@ -201,6 +201,9 @@ impl InsertValues {
     }
 
     /// Extracts column names from the values (sorted alphabetically).
+    ///
+    /// For bulk inserts this is the union of the keys of every row, so a key
+    /// appearing only in later rows still gets a column.
     pub fn get_columns(&self) -> Vec<String> {
         match self {
             InsertValues::Single(map) => {
@@ -208,15 +211,12 @@ impl InsertValues {
                 cols.sort();
                 cols
             }
-            InsertValues::Bulk(rows) => {
-                if let Some(first) = rows.first() {
-                    let mut cols: Vec<String> = first.keys().cloned().collect();
-                    cols.sort();
-                    cols
-                } else {
-                    Vec::new()
-                }
-            }
+            InsertValues::Bulk(rows) => rows
+                .iter()
+                .flat_map(|row| row.keys().cloned())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
         }
     }
 }
@@ -703,4 +703,16 @@ mod tests {
         let columns = values.get_columns();
         assert_eq!(columns.len(), 2);
     }
+
+    #[test]
+    fn test_bulk_insert_columns_are_the_union_of_rows() {
+        let values = InsertValues::Bulk(vec![
+            HashMap::from([("name".to_string(), json!("Alice"))]),
+            HashMap::from([
+                ("name".to_string(), json!("Bob")),
+                ("email".to_string(), json!("bob@example.com")),
+            ]),
+        ]);
+        assert_eq!(values.get_columns(), vec!["email", "name"]);
+    }
 }
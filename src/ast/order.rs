@@ -20,6 +20,9 @@ pub struct OrderTerm {
     pub field: Field,
     pub direction: Direction,
     pub nulls: Option<Nulls>,
+    /// Embedded to-one resource the field belongs to (`clients(name).desc`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation: Option<String>,
 }
 
 impl OrderTerm {
@@ -28,6 +31,7 @@ impl OrderTerm {
             field,
             direction: Direction::Asc,
             nulls: None,
+            relation: None,
         }
     }
 
@@ -41,6 +45,11 @@ impl OrderTerm {
         self
     }
 
+    pub fn with_relation(mut self, relation: impl Into<String>) -> Self {
+        self.relation = Some(relation.into());
+        self
+    }
+
     pub fn desc(mut self) -> Self {
         self.direction = Direction::Desc;
         self
@@ -84,5 +93,12 @@ mod tests {
         let term = OrderTerm::new(field).desc();
         let json = serde_json::to_string(&term).unwrap();
         assert!(json.contains("desc"));
+        assert!(!json.contains("relation"));
+    }
+
+    #[test]
+    fn test_order_term_with_relation() {
+        let term = OrderTerm::new(Field::new("name")).with_relation("clients");
+        assert_eq!(term.relation, Some("clients".to_string()));
     }
 }
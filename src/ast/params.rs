@@ -1,5 +1,6 @@
 use super::{LogicCondition, OrderTerm, SelectItem};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ParsedParams {
@@ -8,6 +9,40 @@ pub struct ParsedParams {
     pub order: Vec<OrderTerm>,
     pub limit: Option<u64>,
     pub offset: Option<u64>,
+    /// Filters, ordering and pagination for embedded resources, keyed by the
+    /// relation name (or alias) used in `select`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub embedded: BTreeMap<String, EmbedParams>,
+}
+
+/// Query parameters scoped to an embedded resource.
+///
+/// Built from keys prefixed with the embed path, such as
+/// `posts.status=eq.published`, `posts.order=created_at.desc` or
+/// `posts.comments.limit=5`. They are applied inside the embedded subquery and
+/// never filter the parent rows.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct EmbedParams {
+    pub filters: Vec<LogicCondition>,
+    pub order: Vec<OrderTerm>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub embedded: BTreeMap<String, EmbedParams>,
+}
+
+impl EmbedParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+            && self.order.is_empty()
+            && self.limit.is_none()
+            && self.offset.is_none()
+            && self.embedded.is_empty()
+    }
 }
 
 impl ParsedParams {
@@ -18,6 +53,7 @@ impl ParsedParams {
             order: Vec::new(),
             limit: None,
             offset: None,
+            embedded: BTreeMap::new(),
         }
     }
 
@@ -46,12 +82,18 @@ impl ParsedParams {
         self
     }
 
+    pub fn with_embedded(mut self, embedded: BTreeMap<String, EmbedParams>) -> Self {
+        self.embedded = embedded;
+        self
+    }
+
     pub fn is_empty(&self) -> bool {
         self.select.is_none()
             && self.filters.is_empty()
             && self.order.is_empty()
             && self.limit.is_none()
             && self.offset.is_none()
+            && self.embedded.is_empty()
     }
 
     pub fn has_filters(&self) -> bool {
@@ -132,5 +174,22 @@ mod tests {
         let params = ParsedParams::new().with_limit(10);
         let json = serde_json::to_string(&params).unwrap();
         assert!(json.contains("limit"));
+        assert!(!json.contains("embedded"));
+    }
+
+    #[test]
+    fn test_parsed_params_with_embedded() {
+        let mut embedded = BTreeMap::new();
+        embedded.insert(
+            "posts".to_string(),
+            EmbedParams {
+                limit: Some(5),
+                ..EmbedParams::new()
+            },
+        );
+        let params = ParsedParams::new().with_embedded(embedded);
+        assert!(!params.is_empty());
+        assert_eq!(params.embedded["posts"].limit, Some(5));
+        assert!(EmbedParams::new().is_empty());
     }
 }
@@ -13,6 +13,17 @@ pub enum ReturnRepresentation {
     HeadersOnly,
 }
 
+impl ReturnRepresentation {
+    /// The preference as written in `Prefer` and `Preference-Applied` headers
+    pub fn as_preference(&self) -> &'static str {
+        match self {
+            ReturnRepresentation::Full => "return=representation",
+            ReturnRepresentation::Minimal => "return=minimal",
+            ReturnRepresentation::HeadersOnly => "return=headers-only",
+        }
+    }
+}
+
 /// Resolution preference for INSERT conflicts
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -23,6 +34,16 @@ pub enum Resolution {
     IgnoreDuplicates,
 }
 
+impl Resolution {
+    /// The preference as written in `Prefer` and `Preference-Applied` headers
+    pub fn as_preference(&self) -> &'static str {
+        match self {
+            Resolution::MergeDuplicates => "resolution=merge-duplicates",
+            Resolution::IgnoreDuplicates => "resolution=ignore-duplicates",
+        }
+    }
+}
+
 /// Count preference for SELECT queries
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -57,6 +78,33 @@ pub enum Missing {
     Null,
 }
 
+impl Missing {
+    /// The preference as written in `Prefer` and `Preference-Applied` headers
+    pub fn as_preference(&self) -> &'static str {
+        match self {
+            Missing::Default => "missing=default",
+            Missing::Null => "missing=null",
+        }
+    }
+}
+
+/// How a function call receives the request body (`Prefer: params=...`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParamsMode {
+    /// Pass the whole JSON body as the function's single argument
+    SingleObject,
+}
+
+impl ParamsMode {
+    /// The preference as written in `Prefer` and `Preference-Applied` headers
+    pub fn as_preference(&self) -> &'static str {
+        match self {
+            ParamsMode::SingleObject => "params=single-object",
+        }
+    }
+}
+
 /// PostgREST Prefer header options
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PreferOptions {
@@ -65,6 +113,7 @@ pub struct PreferOptions {
     pub count: Option<Count>,
     pub plurality: Option<Plurality>,
     pub missing: Option<Missing>,
+    pub params: Option<ParamsMode>,
 }
 
 impl PreferOptions {
@@ -75,6 +124,7 @@ impl PreferOptions {
             count: None,
             plurality: None,
             missing: None,
+            params: None,
         }
     }
 
@@ -103,12 +153,18 @@ impl PreferOptions {
         self
     }
 
+    pub fn with_params(mut self, params: ParamsMode) -> Self {
+        self.params = Some(params);
+        self
+    }
+
     pub fn is_empty(&self) -> bool {
         self.return_representation.is_none()
             && self.resolution.is_none()
             && self.count.is_none()
             && self.plurality.is_none()
             && self.missing.is_none()
+            && self.params.is_none()
     }
 }
 
@@ -127,6 +183,18 @@ mod tests {
         assert_eq!(ReturnRepresentation::default(), ReturnRepresentation::Full);
     }
 
+    #[test]
+    fn test_return_representation_as_preference() {
+        assert_eq!(
+            ReturnRepresentation::Full.as_preference(),
+            "return=representation"
+        );
+        assert_eq!(
+            ReturnRepresentation::HeadersOnly.as_preference(),
+            "return=headers-only"
+        );
+    }
+
     #[test]
     fn test_plurality_default() {
         assert_eq!(Plurality::default(), Plurality::Multiple);
@@ -0,0 +1,167 @@
+use super::ParsedParams;
+use serde::{Deserialize, Serialize};
+
+/// Rows requested with the `Range` header, e.g. `Range: 0-24` with
+/// `Range-Unit: items`.
+///
+/// It is an alternative to `limit` and `offset`: when the query string has
+/// them too, the query string wins (see [`RowRange::merge_into`]).
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::{parse_query_string, parse_range_header};
+///
+/// let range = parse_range_header("10-19", Some("items")).unwrap();
+/// assert_eq!(range.offset, 10);
+/// assert_eq!(range.limit, Some(10));
+///
+/// let mut params = parse_query_string("limit=5").unwrap();
+/// range.merge_into(&mut params);
+/// assert_eq!(params.limit, Some(5));
+/// assert_eq!(params.offset, Some(10));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowRange {
+    /// First requested row (the lower bound of the range)
+    pub offset: u64,
+    /// Number of requested rows, or None for an open range such as `10-`
+    pub limit: Option<u64>,
+}
+
+impl RowRange {
+    pub fn new(offset: u64, limit: Option<u64>) -> Self {
+        Self { offset, limit }
+    }
+
+    /// Applies the range to parsed parameters, keeping the query string's own
+    /// `limit` and `offset` when present
+    pub fn merge_into(&self, params: &mut ParsedParams) {
+        params.offset = params.offset.or(Some(self.offset));
+        params.limit = params.limit.or(self.limit);
+    }
+}
+
+/// The `Content-Range` header and status code of a paginated response.
+///
+/// - `206 Partial Content` when the page holds fewer rows than the total
+/// - `416 Range Not Satisfiable` when the offset is past the total
+/// - `200 OK` otherwise, including when no total was counted
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::ContentRange;
+///
+/// let range = ContentRange::new(0, 25, Some(120));
+/// assert_eq!(range.status, 206);
+/// assert_eq!(range.header, "0-24/120");
+///
+/// assert_eq!(ContentRange::new(0, 3, None).header, "0-2/*");
+/// assert_eq!(ContentRange::new(200, 0, Some(120)).status, 416);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentRange {
+    /// HTTP status code: 200, 206 or 416
+    pub status: u16,
+    /// Value of the `Content-Range` header, e.g. `0-24/120` or `*/0`
+    pub header: String,
+}
+
+impl ContentRange {
+    /// Computes the response range from the offset of the page, the number of
+    /// rows it holds and the total row count, if one was requested
+    pub fn new(offset: u64, page_total: u64, total: Option<u64>) -> Self {
+        let last = page_total
+            .checked_sub(1)
+            .and_then(|rows| offset.checked_add(rows));
+        let range = match last {
+            Some(upper) if total != Some(0) => format!("{}-{}", offset, upper),
+            _ => "*".to_string(),
+        };
+        let total_str = total.map_or_else(|| "*".to_string(), |total| total.to_string());
+
+        let status = match total {
+            Some(total) if offset > total => 416,
+            Some(total) if page_total < total => 206,
+            _ => 200,
+        };
+
+        Self {
+            status,
+            header: format!("{}/{}", range, total_str),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_query_limit_and_offset() {
+        let mut params = ParsedParams::new().with_limit(5).with_offset(1);
+        RowRange::new(10, Some(10)).merge_into(&mut params);
+        assert_eq!(params.limit, Some(5));
+        assert_eq!(params.offset, Some(1));
+
+        let mut params = ParsedParams::new();
+        RowRange::new(10, None).merge_into(&mut params);
+        assert_eq!(params.limit, None);
+        assert_eq!(params.offset, Some(10));
+    }
+
+    #[test]
+    fn test_content_range_full_page() {
+        let range = ContentRange::new(0, 3, Some(3));
+        assert_eq!(range.status, 200);
+        assert_eq!(range.header, "0-2/3");
+    }
+
+    #[test]
+    fn test_content_range_partial_page() {
+        let range = ContentRange::new(10, 10, Some(95));
+        assert_eq!(range.status, 206);
+        assert_eq!(range.header, "10-19/95");
+    }
+
+    #[test]
+    fn test_content_range_without_total() {
+        let range = ContentRange::new(5, 10, None);
+        assert_eq!(range.status, 200);
+        assert_eq!(range.header, "5-14/*");
+    }
+
+    #[test]
+    fn test_content_range_empty_results() {
+        let range = ContentRange::new(0, 0, Some(0));
+        assert_eq!(range.status, 200);
+        assert_eq!(range.header, "*/0");
+
+        let range = ContentRange::new(0, 0, None);
+        assert_eq!(range.header, "*/*");
+
+        // An empty page right after the last row is partial, not unsatisfiable
+        let range = ContentRange::new(20, 0, Some(20));
+        assert_eq!(range.status, 206);
+        assert_eq!(range.header, "*/20");
+    }
+
+    #[test]
+    fn test_content_range_near_u64_max() {
+        let range = ContentRange::new(u64::MAX, 1, None);
+        assert_eq!(range.status, 200);
+        assert_eq!(range.header, format!("{0}-{0}/*", u64::MAX));
+
+        let range = ContentRange::new(u64::MAX, 0, Some(10));
+        assert_eq!(range.status, 416);
+        assert_eq!(range.header, "*/10");
+    }
+
+    #[test]
+    fn test_content_range_not_satisfiable() {
+        let range = ContentRange::new(21, 0, Some(20));
+        assert_eq!(range.status, 416);
+        assert_eq!(range.header, "*/20");
+    }
+}
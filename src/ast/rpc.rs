@@ -64,6 +64,71 @@ pub struct RpcParams {
     pub offset: Option<u64>,
     /// Optional columns to select from function results
     pub returning: Option<Vec<SelectItem>>,
+    /// How the request body is passed to the function
+    pub body: RpcBody,
+}
+
+/// How the request body of an RPC call reaches the function.
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::{RpcBody, RpcParams};
+/// use std::collections::HashMap;
+/// use serde_json::json;
+///
+/// // Prefer: params=single-object, or a function with one unnamed json parameter
+/// let params = RpcParams::new("ingest", HashMap::new())
+///     .with_body(RpcBody::SingleObject(json!({"events": [1, 2]})));
+/// assert!(params.args.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcBody {
+    /// The keys of a JSON object body are named arguments, see [`RpcParams::args`]
+    #[default]
+    Named,
+    /// The whole JSON body is the function's single argument
+    SingleObject(Value),
+    /// A raw `text/plain` or `application/octet-stream` body, passed as the
+    /// single `text` or `bytea` argument
+    Raw(RawMediaType, String),
+    /// A JSON array body: the function is called once per object, whose keys
+    /// are named arguments, and the results are combined
+    Batch(Vec<HashMap<String, Value>>),
+}
+
+/// Media type of a raw RPC body, see [`RpcBody::Raw`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawMediaType {
+    /// `text/plain`
+    Text,
+    /// `application/octet-stream`
+    Binary,
+}
+
+impl RawMediaType {
+    /// Reads the media type of a `Content-Type` header, ignoring its parameters;
+    /// `None` for bodies that are not raw
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        if essence.eq_ignore_ascii_case("text/plain") {
+            Some(RawMediaType::Text)
+        } else if essence.eq_ignore_ascii_case("application/octet-stream") {
+            Some(RawMediaType::Binary)
+        } else {
+            None
+        }
+    }
+
+    /// The type of the parameter a body of this media type is passed to
+    pub fn param_type(&self) -> &'static str {
+        match self {
+            RawMediaType::Text => "text",
+            RawMediaType::Binary => "bytea",
+        }
+    }
 }
 
 impl RpcParams {
@@ -77,6 +142,7 @@ impl RpcParams {
             limit: None,
             offset: None,
             returning: None,
+            body: RpcBody::Named,
         }
     }
 
@@ -109,6 +175,12 @@ impl RpcParams {
         self.returning = Some(returning);
         self
     }
+
+    /// Sets how the request body is passed to the function.
+    pub fn with_body(mut self, body: RpcBody) -> Self {
+        self.body = body;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +201,7 @@ mod tests {
         assert_eq!(params.limit, None);
         assert_eq!(params.offset, None);
         assert_eq!(params.returning, None);
+        assert_eq!(params.body, RpcBody::Named);
     }
 
     #[test]
@@ -140,6 +140,200 @@ impl Relationship {
     }
 }
 
+/// A database function callable through `/rpc/<name>`, as introspected into the
+/// schema cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Function {
+    pub schema: String,
+    pub name: String,
+    /// Input parameters, in declaration order
+    pub params: Vec<FunctionParam>,
+    pub volatility: Volatility,
+    pub returns: FunctionReturn,
+}
+
+impl Function {
+    pub fn new(schema: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            schema: schema.into(),
+            name: name.into(),
+            params: Vec::new(),
+            volatility: Volatility::Volatile,
+            returns: FunctionReturn::void(),
+        }
+    }
+
+    pub fn with_params(mut self, params: Vec<FunctionParam>) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn with_volatility(mut self, volatility: Volatility) -> Self {
+        self.volatility = volatility;
+        self
+    }
+
+    pub fn returning(mut self, returns: FunctionReturn) -> Self {
+        self.returns = returns;
+        self
+    }
+
+    /// Returns true if the function has an input parameter with this name
+    pub fn has_param(&self, name: &str) -> bool {
+        self.param(name).is_some()
+    }
+
+    /// The input parameter with this name
+    pub fn param(&self, name: &str) -> Option<&FunctionParam> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    /// Returns true if a call with these named arguments reaches this overload,
+    /// following PostgREST: every argument names a parameter and every parameter
+    /// without a default is given
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use postgrest_parser::{Function, FunctionParam};
+    ///
+    /// let search = Function::new("public", "search").with_params(vec![
+    ///     FunctionParam::new("term", "text"),
+    ///     FunctionParam::new("max_price", "numeric").with_default(true),
+    /// ]);
+    /// assert!(search.accepts(&["term"]));
+    /// assert!(search.accepts(&["term", "max_price"]));
+    /// assert!(!search.accepts(&["max_price"]));
+    /// assert!(!search.accepts(&["term", "category"]));
+    /// ```
+    pub fn accepts(&self, arg_names: &[&str]) -> bool {
+        arg_names.iter().all(|name| self.has_param(name))
+            && self
+                .params
+                .iter()
+                .all(|param| param.has_default || arg_names.contains(&param.name.as_str()))
+    }
+
+    /// The function's signature as shown in error messages, e.g.
+    /// `public.search(term text, max_price numeric)`
+    pub fn signature(&self) -> String {
+        let params = self
+            .params
+            .iter()
+            .map(|param| {
+                let variadic = if param.variadic { "VARIADIC " } else { "" };
+                if param.name.is_empty() {
+                    format!("{}{}", variadic, param.type_)
+                } else {
+                    format!("{}{} {}", variadic, param.name, param.type_)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}.{}({})", self.schema, self.name, params)
+    }
+
+    /// The function's only parameter when it is unnamed, as in `f(json)`; such a
+    /// parameter receives the whole request body
+    pub fn single_unnamed_param(&self) -> Option<&FunctionParam> {
+        match self.params.as_slice() {
+            [param] if param.name.is_empty() => Some(param),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionParam {
+    /// Parameter name, empty for unnamed parameters
+    pub name: String,
+    pub type_: String,
+    pub has_default: bool,
+    /// True for a `VARIADIC` parameter; its type is the array type, e.g. `integer[]`
+    pub variadic: bool,
+}
+
+impl FunctionParam {
+    pub fn new(name: impl Into<String>, type_: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            type_: type_.into(),
+            has_default: false,
+            variadic: false,
+        }
+    }
+
+    pub fn with_default(mut self, has_default: bool) -> Self {
+        self.has_default = has_default;
+        self
+    }
+
+    pub fn as_variadic(mut self) -> Self {
+        self.variadic = true;
+        self
+    }
+}
+
+/// Function volatility; only `IMMUTABLE` and `STABLE` functions can be called with GET
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Volatility {
+    Immutable,
+    Stable,
+    Volatile,
+}
+
+/// What a function returns
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionReturn {
+    /// Return type name, e.g. `integer`, `products` or `record`
+    pub type_: String,
+    pub kind: ReturnKind,
+    /// True for `SETOF ...` and `TABLE (...)` returns
+    pub set_of: bool,
+}
+
+impl FunctionReturn {
+    pub fn scalar(type_: impl Into<String>) -> Self {
+        Self {
+            type_: type_.into(),
+            kind: ReturnKind::Scalar,
+            set_of: false,
+        }
+    }
+
+    pub fn composite(type_: impl Into<String>) -> Self {
+        Self {
+            type_: type_.into(),
+            kind: ReturnKind::Composite,
+            set_of: false,
+        }
+    }
+
+    pub fn void() -> Self {
+        Self {
+            type_: "void".to_string(),
+            kind: ReturnKind::Void,
+            set_of: false,
+        }
+    }
+
+    pub fn set_of(mut self) -> Self {
+        self.set_of = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReturnKind {
+    /// A base, domain, enum or array type
+    Scalar,
+    /// A table row type, composite type or `record`
+    Composite,
+    Void,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,4 +422,38 @@ mod tests {
         let json = serde_json::to_string(&rel).unwrap();
         assert!(json.contains("many_to_one"));
     }
+
+    #[test]
+    fn test_function_new() {
+        let function = Function::new("public", "search")
+            .with_params(vec![
+                FunctionParam::new("term", "text"),
+                FunctionParam::new("max_price", "numeric").with_default(true),
+            ])
+            .with_volatility(Volatility::Stable)
+            .returning(FunctionReturn::composite("products").set_of());
+
+        assert!(function.has_param("term"));
+        assert!(!function.has_param("price"));
+        assert_eq!(function.returns.kind, ReturnKind::Composite);
+        assert!(function.returns.set_of);
+        assert!(function.single_unnamed_param().is_none());
+
+        let ingest =
+            Function::new("public", "ingest").with_params(vec![FunctionParam::new("", "jsonb")]);
+        assert_eq!(ingest.single_unnamed_param().unwrap().type_, "jsonb");
+    }
+
+    #[test]
+    fn test_function_signature() {
+        let function = Function::new("public", "tag").with_params(vec![
+            FunctionParam::new("", "integer"),
+            FunctionParam::new("labels", "text[]").as_variadic(),
+        ]);
+        assert_eq!(
+            function.signature(),
+            "public.tag(integer, VARIADIC labels text[])"
+        );
+        assert_eq!(Function::new("api", "ping").signature(), "api.ping()");
+    }
 }
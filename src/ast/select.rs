@@ -1,3 +1,4 @@
+use super::{Field, JsonOp};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -17,6 +18,75 @@ pub enum ItemHint {
     Cast(String),
 }
 
+/// Aggregate function applied to a select-list column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// Parses a PostgREST aggregate name (`count`, `sum`, `avg`, `min`, `max`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "count" => Some(Self::Count),
+            "sum" => Some(Self::Sum),
+            "avg" => Some(Self::Avg),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            _ => None,
+        }
+    }
+
+    /// Returns the SQL function name, which is also the default output name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+}
+
+/// An aggregate select item such as `price.sum()` or `count()::text`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    /// Cast applied to the aggregate result (`price.sum()::int`)
+    pub cast: Option<String>,
+}
+
+impl Aggregate {
+    pub fn new(function: AggregateFunction) -> Self {
+        Self {
+            function,
+            cast: None,
+        }
+    }
+
+    pub fn with_cast(mut self, cast: impl Into<String>) -> Self {
+        self.cast = Some(cast.into());
+        self
+    }
+}
+
+/// How an embedded resource is joined to its parent.
+///
+/// `Left` (the default, or `!left`) keeps every parent row; `Inner` (`!inner`)
+/// keeps only parent rows that have at least one matching embedded row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinType {
+    Left,
+    Inner,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SelectItem {
     pub item_type: ItemType,
@@ -24,6 +94,12 @@ pub struct SelectItem {
     pub alias: Option<String>,
     pub children: Option<Vec<SelectItem>>,
     pub hint: Option<ItemHint>,
+    /// Column with its JSON path and cast, for fields that carry either
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<Field>,
+    /// Aggregate applied to the column; `count()` aggregates the `*` field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<Aggregate>,
 }
 
 impl SelectItem {
@@ -34,6 +110,8 @@ impl SelectItem {
             alias: None,
             children: None,
             hint: None,
+            field: None,
+            aggregate: None,
         }
     }
 
@@ -52,6 +130,21 @@ impl SelectItem {
         self
     }
 
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.field = Some(field);
+        self
+    }
+
+    pub fn with_aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregate = Some(aggregate);
+        self
+    }
+
+    /// Returns true for aggregate columns such as `price.sum()`
+    pub fn is_aggregate(&self) -> bool {
+        self.aggregate.is_some()
+    }
+
     pub fn relation(name: impl Into<String>) -> Self {
         Self {
             item_type: ItemType::Relation,
@@ -59,6 +152,8 @@ impl SelectItem {
             alias: None,
             children: None,
             hint: None,
+            field: None,
+            aggregate: None,
         }
     }
 
@@ -69,12 +164,60 @@ impl SelectItem {
             alias: None,
             children: None,
             hint: None,
+            field: None,
+            aggregate: None,
         }
     }
 
     pub fn wildcard() -> Self {
         Self::field("*".to_string())
     }
+
+    /// Returns the column name used in the result.
+    ///
+    /// Follows PostgREST: the alias when given, otherwise the aggregate function
+    /// (`price.sum()` is named `sum`), otherwise the last key of a JSON path
+    /// (`data->address->>city` is named `city`), otherwise the column name.
+    pub fn output_name(&self) -> &str {
+        if let Some(alias) = &self.alias {
+            return alias;
+        }
+        if let Some(aggregate) = &self.aggregate {
+            return aggregate.function.name();
+        }
+
+        match self.field.as_ref().and_then(|field| field.json_path.last()) {
+            Some(JsonOp::Arrow(key) | JsonOp::DoubleArrow(key)) => key,
+            _ => &self.name,
+        }
+    }
+
+    /// Returns the relationship hint of an embed (`addresses!billing_address_id`),
+    /// ignoring the `!inner` / `!left` join hints it may be combined with.
+    pub fn relation_hint(&self) -> Option<&str> {
+        match &self.hint {
+            Some(ItemHint::Inner(hint)) => hint.split('!').find(|part| {
+                !part.is_empty()
+                    && !part.eq_ignore_ascii_case("inner")
+                    && !part.eq_ignore_ascii_case("left")
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the join requested by the item's `!inner` / `!left` hint.
+    pub fn join_type(&self) -> JoinType {
+        match &self.hint {
+            Some(ItemHint::Inner(hint))
+                if hint
+                    .split('!')
+                    .any(|part| part.eq_ignore_ascii_case("inner")) =>
+            {
+                JoinType::Inner
+            }
+            _ => JoinType::Left,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -110,6 +253,71 @@ mod tests {
         assert_eq!(item.item_type, ItemType::Field);
     }
 
+    #[test]
+    fn test_select_item_join_type() {
+        let item = SelectItem::relation("clients");
+        assert_eq!(item.join_type(), JoinType::Left);
+
+        let item = item.with_hint(ItemHint::Inner("inner".to_string()));
+        assert_eq!(item.join_type(), JoinType::Inner);
+
+        let item = SelectItem::relation("clients").with_hint(ItemHint::Inner("left".to_string()));
+        assert_eq!(item.join_type(), JoinType::Left);
+
+        let item = SelectItem::relation("clients")
+            .with_hint(ItemHint::Inner("clients_id_fkey!inner".to_string()));
+        assert_eq!(item.join_type(), JoinType::Inner);
+    }
+
+    #[test]
+    fn test_select_item_relation_hint() {
+        let hinted = |hint: &str| {
+            SelectItem::relation("addresses").with_hint(ItemHint::Inner(hint.to_string()))
+        };
+
+        assert_eq!(SelectItem::relation("addresses").relation_hint(), None);
+        assert_eq!(hinted("inner").relation_hint(), None);
+        assert_eq!(
+            hinted("billing_address_id").relation_hint(),
+            Some("billing_address_id")
+        );
+        assert_eq!(
+            hinted("orders_billing_address_id_fkey!inner").relation_hint(),
+            Some("orders_billing_address_id_fkey")
+        );
+    }
+
+    #[test]
+    fn test_select_item_output_name() {
+        assert_eq!(SelectItem::field("id").output_name(), "id");
+        assert_eq!(
+            SelectItem::field("id").with_alias("key").output_name(),
+            "key"
+        );
+
+        let city = SelectItem::field("data").with_field(Field::new("data").with_json_path(vec![
+            JsonOp::Arrow("address".to_string()),
+            JsonOp::DoubleArrow("city".to_string()),
+        ]));
+        assert_eq!(city.output_name(), "city");
+
+        let price = SelectItem::field("price").with_field(Field::new("price").with_cast("text"));
+        assert_eq!(price.output_name(), "price");
+
+        let total =
+            SelectItem::field("price").with_aggregate(Aggregate::new(AggregateFunction::Sum));
+        assert_eq!(total.output_name(), "sum");
+        assert!(total.is_aggregate());
+    }
+
+    #[test]
+    fn test_aggregate_function_names() {
+        for name in ["count", "sum", "avg", "min", "max"] {
+            assert_eq!(AggregateFunction::from_name(name).unwrap().name(), name);
+        }
+        assert!(AggregateFunction::from_name("median").is_none());
+    }
+
     #[test]
     fn test_select_item_spread() {
         let item = SelectItem::spread("profile");
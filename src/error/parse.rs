@@ -86,6 +86,9 @@ pub enum ParseError {
     #[error("invalid offset value: {0}")]
     InvalidOffset(String),
 
+    #[error("invalid Range header: {0}")]
+    InvalidRange(String),
+
     #[error("invalid integer value: {0}")]
     InvalidInteger(String),
 
@@ -111,6 +114,12 @@ pub enum ParseError {
     #[error("unsupported HTTP method: {0}")]
     UnsupportedMethod(String),
 
+    #[error("cannot call volatile function '{0}' with GET, use POST")]
+    VolatileFunctionGet(String),
+
+    #[error("parameter '{1}' is VARIADIC in some overloads of function '{0}' and not in others")]
+    AmbiguousVariadicParameter(String, String),
+
     #[error("invalid schema name: {0}")]
     InvalidSchema(String),
 
@@ -8,6 +8,7 @@ pub enum SqlError {
     #[error("relationship not found: {0}")]
     RelationshipNotFound(String),
 
+    #[deprecated(note = "use AmbiguousRelationship")]
     #[error("relationship is ambiguous, use hint: {0}")]
     RelationshipAmbiguous(String),
 
@@ -70,8 +71,59 @@ pub enum SqlError {
         to_table: String,
     },
 
-    #[error("many-to-many relationships not yet supported (junction table: '{junction_table}')")]
-    ManyToManyNotYetSupported { junction_table: String },
+    #[error(
+        "could not embed '{to_table}' in '{from_table}': more than one relationship was found, disambiguate with one of: {}",
+        candidates.join(", ")
+    )]
+    AmbiguousRelationship {
+        from_table: String,
+        to_table: String,
+        candidates: Vec<String>,
+    },
+
+    #[error("'{0}' is a to-many relationship and cannot be spread into the parent row unless it selects only aggregates")]
+    SpreadToMany(String),
+
+    #[error("'{0}' cannot be selected next to aggregates: spread its columns by name so they can be grouped by")]
+    UngroupableRelation(String),
+
+    #[error("'{0}' is not an embedded resource in this request")]
+    EmbedNotSelected(String),
+
+    #[error("cannot order by '{0}': only to-one related resources can be used for ordering")]
+    OrderByToMany(String),
+
+    #[error("ordering by related resource '{0}' requires a schema cache and a table context")]
+    RelatedOrderUnavailable(String),
+
+    #[error("JSON object requested, multiple (or no) rows returned: the result contains {0} rows")]
+    SingularRowCount(i64),
+
+    #[error("function '{0}' does not return a set of rows, its result cannot be filtered, ordered or paginated")]
+    FunctionNotSetReturning(String),
+
+    #[error("calling function '{0}' with an array of argument objects requires its parameter types from the schema cache")]
+    FunctionSignatureRequired(String),
+
+    #[error(
+        "could not find function '{function}' with the arguments ({}), candidates are: {}",
+        arguments.join(", "),
+        candidates.join(", ")
+    )]
+    FunctionNotFound {
+        function: String,
+        arguments: Vec<String>,
+        candidates: Vec<String>,
+    },
+
+    #[error(
+        "could not choose the overload of function '{function}' to call, candidates are: {}",
+        candidates.join(", ")
+    )]
+    AmbiguousFunction {
+        function: String,
+        candidates: Vec<String>,
+    },
 }
 
 #[cfg(test)]
@@ -99,8 +151,41 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_sql_error_relationship_ambiguous() {
         let err = SqlError::RelationshipAmbiguous("client".to_string());
         assert!(err.to_string().contains("ambiguous"));
     }
+
+    #[test]
+    fn test_sql_error_ambiguous_relationship_lists_candidates() {
+        let err = SqlError::AmbiguousRelationship {
+            from_table: "orders".to_string(),
+            to_table: "addresses".to_string(),
+            candidates: vec![
+                "addresses!orders_billing_address_id_fkey".to_string(),
+                "addresses!orders_shipping_address_id_fkey".to_string(),
+            ],
+        };
+        assert_eq!(
+            err.to_string(),
+            "could not embed 'addresses' in 'orders': more than one relationship was found, disambiguate with one of: addresses!orders_billing_address_id_fkey, addresses!orders_shipping_address_id_fkey"
+        );
+    }
+
+    #[test]
+    fn test_sql_error_function_not_found_lists_candidates() {
+        let err = SqlError::FunctionNotFound {
+            function: "search".to_string(),
+            arguments: vec!["limit".to_string()],
+            candidates: vec![
+                "public.search(term text)".to_string(),
+                "public.search(term text, max integer)".to_string(),
+            ],
+        };
+        assert_eq!(
+            err.to_string(),
+            "could not find function 'search' with the arguments (limit), candidates are: public.search(term text), public.search(term text, max integer)"
+        );
+    }
 }
@@ -118,6 +118,7 @@
 //! ```
 
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 pub mod ast;
 pub mod error;
@@ -131,21 +132,24 @@ pub mod schema_cache;
 pub mod wasm;
 
 pub use ast::{
-    Cardinality, Column, ConflictAction, Count, DeleteParams, Direction, Field, Filter,
-    FilterOperator, FilterValue, InsertParams, InsertValues, ItemHint, ItemType, JsonOp, Junction,
-    LogicCondition, LogicOperator, LogicTree, Missing, Nulls, OnConflict, Operation, OrderTerm,
-    ParsedParams, Plurality, PreferOptions, Quantifier, Relationship, Resolution, ResolvedTable,
-    ReturnRepresentation, RpcParams, SelectItem, Table, UpdateParams,
+    Aggregate, AggregateFunction, Cardinality, Column, ConflictAction, ContentRange, Count,
+    DeleteParams, Direction, EmbedParams, Field, Filter, FilterOperator, FilterValue, Function,
+    FunctionParam, FunctionReturn, InsertParams, InsertValues, ItemHint, ItemType, JsonOp,
+    Junction, LogicCondition, LogicOperator, LogicTree, Missing, Nulls, OnConflict, Operation,
+    OrderTerm, ParamsMode, ParsedParams, Plurality, PreferOptions, Quantifier, RawMediaType,
+    Relationship, Resolution, ResolvedTable, ReturnKind, ReturnRepresentation, RowRange, RpcBody,
+    RpcParams, SelectItem, Table, UpdateParams, Volatility,
 };
 pub use error::{Error, ParseError, SqlError};
 pub use parser::{
     field, get_profile_header, identifier, json_path, json_path_segment, logic_key,
     parse_delete_params, parse_filter, parse_insert_params, parse_json_body, parse_logic,
-    parse_order, parse_order_term, parse_prefer_header, parse_qualified_table, parse_rpc_params,
-    parse_select, parse_update_params, reserved_key, resolve_schema, type_cast,
+    parse_order, parse_order_term, parse_prefer_header, parse_qualified_table, parse_query_pairs,
+    parse_range_header, parse_rpc_params, parse_rpc_params_with_functions, parse_select,
+    parse_update_params, percent_decode, reserved_key, resolve_schema, type_cast,
     validate_insert_body, validate_update_body,
 };
-pub use sql::{QueryBuilder, QueryResult};
+pub use sql::{CountQuery, QueryBuilder, QueryResult, DEFAULT_ESTIMATED_COUNT_THRESHOLD};
 
 #[cfg(feature = "postgres")]
 pub use schema_cache::{ForeignKey, RelationType, SchemaCache};
@@ -174,19 +178,7 @@ pub use schema_cache::{ForeignKey, RelationType, SchemaCache};
 /// assert_eq!(params.limit, Some(10));
 /// ```
 pub fn parse_query_string(query_string: &str) -> Result<ParsedParams, Error> {
-    let pairs: Vec<(String, String)> = query_string
-        .split('&')
-        .filter_map(|pair| {
-            let parts: Vec<&str> = pair.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                Some((parts[0].to_string(), parts[1].to_string()))
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    parse_params_from_pairs(pairs)
+    parse_params_from_pairs(parse_query_pairs(query_string))
 }
 
 /// Parses query parameters from a HashMap into structured parameters.
@@ -220,7 +212,16 @@ pub fn parse_params(
     let limit = params.get("limit").and_then(|s| s.parse::<u64>().ok());
     let offset = params.get("offset").and_then(|s| s.parse::<u64>().ok());
 
-    let mut parsed = ParsedParams::new().with_filters(filters);
+    let mut embedded = BTreeMap::new();
+    for (key, value) in params {
+        if let Some((path, key)) = parser::filter::embedded_key(key) {
+            parse_embedded_param(&mut embedded, &path, &key, value)?;
+        }
+    }
+
+    let mut parsed = ParsedParams::new()
+        .with_filters(filters)
+        .with_embedded(embedded);
 
     if let Some(select_str) = select_str {
         parsed = parsed.with_select(parse_select(&select_str)?);
@@ -246,11 +247,15 @@ pub fn parse_params_from_pairs(pairs: Vec<(String, String)>) -> Result<ParsedPar
     // But keep ALL filter pairs to support multiple filters on same column
     let mut single_value_map = std::collections::HashMap::new();
     let mut filter_pairs = Vec::new();
+    let mut embedded = BTreeMap::new();
 
     for (key, value) in pairs {
         if parser::filter::reserved_key(&key) {
             // Reserved keys: select, order, limit, offset - only keep last value
             single_value_map.insert(key, value);
+        } else if let Some((path, key)) = parser::filter::embedded_key(&key) {
+            // Embedded keys (posts.status, posts.order): scoped to the embed subquery
+            parse_embedded_param(&mut embedded, &path, &key, &value)?;
         } else {
             // Filter keys: keep all pairs to support multiple filters on same column
             filter_pairs.push((key, value));
@@ -270,7 +275,9 @@ pub fn parse_params_from_pairs(pairs: Vec<(String, String)>) -> Result<ParsedPar
     // Parse filters from pairs (supports multiple filters on same column)
     let filters = parse_filters_from_pairs(&filter_pairs)?;
 
-    let mut parsed = ParsedParams::new().with_filters(filters);
+    let mut parsed = ParsedParams::new()
+        .with_filters(filters)
+        .with_embedded(embedded);
 
     if let Some(select_str) = select_str {
         parsed = parsed.with_select(parse_select(&select_str)?);
@@ -401,7 +408,8 @@ pub fn build_filter_clause(filters: &[LogicCondition]) -> Result<FilterClauseRes
 /// * `table` - Table name, optionally schema-qualified (e.g., "users" or "auth.users")
 /// * `query_string` - Query parameters
 /// * `body` - Optional JSON body for mutations
-/// * `headers` - Optional headers for schema resolution
+/// * `headers` - Optional headers: `Accept-Profile`/`Content-Profile` for schema
+///   resolution, `Prefer`, `Accept`, and `Range`/`Range-Unit` for GET pagination
 ///
 /// # Examples
 ///
@@ -431,47 +439,21 @@ pub fn parse(
 ) -> Result<Operation, Error> {
     // Check if this is an RPC call (table starts with "rpc/")
     if let Some(function_name) = table.strip_prefix("rpc/") {
-        // Validate function name
-        if function_name.is_empty() {
-            return Err(Error::Parse(ParseError::InvalidTableName(
-                "RPC function name cannot be empty".to_string(),
-            )));
-        }
-
-        // Validate schema for RPC
-        let _resolved_table = resolve_schema(function_name, method, headers)?;
-
-        // Extract Prefer header
-        let prefer = headers
-            .and_then(|h| {
-                h.get("Prefer")
-                    .or_else(|| h.get("prefer"))
-                    .or_else(|| h.get("PREFER"))
-            })
-            .map(|p| parse_prefer_header(p))
-            .transpose()?;
-
-        // Parse RPC parameters (supports both GET and POST)
-        let params = parse_rpc_params(function_name, query_string, body)?;
-        return Ok(Operation::Rpc(params, prefer));
+        return parse_rpc(method, function_name, query_string, body, headers, |_| &[]);
     }
 
     // Validate table name and schema (result used for validation)
     let _resolved_table = resolve_schema(table, method, headers)?;
 
-    // Extract Prefer header (case-insensitive)
-    let prefer = headers
-        .and_then(|h| {
-            h.get("Prefer")
-                .or_else(|| h.get("prefer"))
-                .or_else(|| h.get("PREFER"))
-        })
-        .map(|p| parse_prefer_header(p))
-        .transpose()?;
+    let prefer = extract_prefer(headers)?;
 
     match method.to_uppercase().as_str() {
         "GET" => {
-            let params = parse_query_string(query_string)?;
+            let mut params = parse_query_string(query_string)?;
+            if let Some(range) = get_header(headers, "Range") {
+                let unit = get_header(headers, "Range-Unit");
+                parse_range_header(range, unit)?.merge_into(&mut params);
+            }
             Ok(Operation::Select(params, prefer))
         }
         "POST" => {
@@ -523,6 +505,149 @@ pub fn parse(
     }
 }
 
+/// Like [`parse`], using function metadata from the schema cache for RPC calls.
+///
+/// On GET, query keys naming a parameter of the function become arguments
+/// instead of filters (see [`parse_rpc_params_with_functions`]), and GET is
+/// rejected for functions that are only known as `VOLATILE`, as PostgREST does.
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::{parse_with_schema_cache, Function, FunctionParam, Operation, SchemaCache, Volatility};
+///
+/// let mut cache = SchemaCache::new();
+/// cache.add_function(
+///     Function::new("public", "search")
+///         .with_params(vec![FunctionParam::new("term", "text")])
+///         .with_volatility(Volatility::Stable),
+/// );
+///
+/// let op = parse_with_schema_cache("GET", "rpc/search", "term=foo&limit=5", None, None, &cache).unwrap();
+/// match op {
+///     Operation::Rpc(params, _) => assert!(params.args.contains_key("term")),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[cfg(feature = "postgres")]
+pub fn parse_with_schema_cache(
+    method: &str,
+    table: &str,
+    query_string: &str,
+    body: Option<&str>,
+    headers: Option<&std::collections::HashMap<String, String>>,
+    cache: &SchemaCache,
+) -> Result<Operation, Error> {
+    match table.strip_prefix("rpc/") {
+        Some(function_name) => parse_rpc(
+            method,
+            function_name,
+            query_string,
+            body,
+            headers,
+            |resolved| cache.get_functions(&resolved.schema, &resolved.name),
+        ),
+        None => parse(method, table, query_string, body, headers),
+    }
+}
+
+/// Parses an RPC call, with the overloads `functions` finds for the resolved function
+fn parse_rpc<'a>(
+    method: &str,
+    function_name: &str,
+    query_string: &str,
+    body: Option<&str>,
+    headers: Option<&std::collections::HashMap<String, String>>,
+    functions: impl FnOnce(&ResolvedTable) -> &'a [Function],
+) -> Result<Operation, Error> {
+    // Validate function name
+    if function_name.is_empty() {
+        return Err(Error::Parse(ParseError::InvalidTableName(
+            "RPC function name cannot be empty".to_string(),
+        )));
+    }
+
+    // Validate schema for RPC
+    let resolved_function = resolve_schema(function_name, method, headers)?;
+    let functions = functions(&resolved_function);
+
+    let is_read = matches!(method.to_uppercase().as_str(), "GET" | "HEAD");
+    if is_read
+        && !functions.is_empty()
+        && functions
+            .iter()
+            .all(|function| function.volatility == Volatility::Volatile)
+    {
+        return Err(Error::Parse(ParseError::VolatileFunctionGet(
+            function_name.to_string(),
+        )));
+    }
+
+    let prefer = extract_prefer(headers)?;
+    let single_object =
+        prefer.as_ref().and_then(|prefer| prefer.params) == Some(ParamsMode::SingleObject);
+
+    // Raw and single-object bodies are one argument, not named ones
+    let raw = get_header(headers, "Content-Type").and_then(RawMediaType::from_content_type);
+    let (body, rpc_body) = match (body, raw) {
+        (Some(body), Some(media_type)) => (None, RpcBody::Raw(media_type, body.to_string())),
+        (Some(body), None) if single_object => {
+            (None, RpcBody::SingleObject(parse_json_body(body)?))
+        }
+        (body, _) => (body, RpcBody::Named),
+    };
+
+    // Parse RPC parameters (supports both GET and POST)
+    let mut params =
+        parse_rpc_params_with_functions(method, function_name, query_string, body, functions)?;
+    if rpc_body != RpcBody::Named {
+        params = params.with_body(rpc_body);
+    }
+    Ok(Operation::Rpc(params, prefer))
+}
+
+/// Media type requesting a single JSON object instead of an array
+const SINGULAR_MEDIA_TYPE: &str = "application/vnd.pgrst.object+json";
+
+/// Looks up a header by its canonical, lowercase or uppercase name
+fn get_header<'a>(
+    headers: Option<&'a std::collections::HashMap<String, String>>,
+    name: &str,
+) -> Option<&'a str> {
+    headers.and_then(|h| {
+        h.get(name)
+            .or_else(|| h.get(&name.to_lowercase()))
+            .or_else(|| h.get(&name.to_uppercase()))
+            .map(String::as_str)
+    })
+}
+
+/// Extracts the Prefer header (case-insensitive), folding in an `Accept` header
+/// asking for a single object as `plurality=singular`
+fn extract_prefer(
+    headers: Option<&std::collections::HashMap<String, String>>,
+) -> Result<Option<PreferOptions>, Error> {
+    let header = |name: &str| get_header(headers, name);
+
+    let prefer = header("Prefer").map(parse_prefer_header).transpose()?;
+
+    let singular = header("Accept").is_some_and(|accept| {
+        accept.split(',').any(|media_type| {
+            let essence = media_type.split(';').next().unwrap_or_default().trim();
+            essence.eq_ignore_ascii_case(SINGULAR_MEDIA_TYPE)
+        })
+    });
+    if singular {
+        return Ok(Some(
+            prefer
+                .unwrap_or_default()
+                .with_plurality(Plurality::Singular),
+        ));
+    }
+
+    Ok(prefer)
+}
+
 /// Converts an Operation to SQL
 ///
 /// # Arguments
@@ -542,43 +667,76 @@ pub fn parse(
 pub fn operation_to_sql(table: &str, operation: &Operation) -> Result<QueryResult, Error> {
     // For SELECT operations, use the simple table name
     // For mutations and RPC, we need to re-resolve the schema
-    // Note: Prefer options are parsed but don't affect SQL generation (future enhancement)
+    // Note: `Prefer: count`, singular plurality, `return`, `missing` and `resolution` affect
+    // SQL generation so far
     match operation {
-        Operation::Select(params, _prefer) => to_sql(table, params),
-        Operation::Insert(params, _prefer) => {
+        Operation::Select(params, prefer) => {
+            let mut builder = QueryBuilder::new();
+            if let Some(count) = prefer.as_ref().and_then(|prefer| prefer.count) {
+                builder = builder.with_count(count);
+            }
+            if prefer.as_ref().and_then(|prefer| prefer.plurality) == Some(Plurality::Singular) {
+                // A single object only makes sense as a wrapped response body
+                builder = builder.with_wrapped_response(Plurality::Singular);
+            }
+            builder.build_select(table, params).map_err(Error::Sql)
+        }
+        Operation::Insert(params, prefer) => {
             // Re-resolve schema for consistency
             let resolved_table = resolve_schema(table, "POST", None)?;
-            let mut builder = QueryBuilder::new();
-            builder
+            mutation_builder(prefer)
                 .build_insert(&resolved_table, params)
                 .map_err(Error::Sql)
         }
-        Operation::Update(params, _prefer) => {
+        Operation::Update(params, prefer) => {
             let resolved_table = resolve_schema(table, "PATCH", None)?;
-            let mut builder = QueryBuilder::new();
-            builder
+            mutation_builder(prefer)
                 .build_update(&resolved_table, params)
                 .map_err(Error::Sql)
         }
-        Operation::Delete(params, _prefer) => {
+        Operation::Delete(params, prefer) => {
             let resolved_table = resolve_schema(table, "DELETE", None)?;
-            let mut builder = QueryBuilder::new();
-            builder
+            mutation_builder(prefer)
                 .build_delete(&resolved_table, params)
                 .map_err(Error::Sql)
         }
-        Operation::Rpc(params, _prefer) => {
+        Operation::Rpc(params, prefer) => {
             // For RPC, table should be the function name (or "rpc/function_name")
             let function_name = table.strip_prefix("rpc/").unwrap_or(table);
             let resolved_table = resolve_schema(function_name, "POST", None)?;
             let mut builder = QueryBuilder::new();
-            builder
+            let mut result = builder
                 .build_rpc(&resolved_table, params)
-                .map_err(Error::Sql)
+                .map_err(Error::Sql)?;
+            let params_mode = prefer.as_ref().and_then(|prefer| prefer.params);
+            if let (Some(mode), RpcBody::SingleObject(_)) = (params_mode, &params.body) {
+                result
+                    .preference_applied
+                    .push(mode.as_preference().to_string());
+            }
+            Ok(result)
         }
     }
 }
 
+/// Creates a builder applying the Prefer options of a mutation
+fn mutation_builder(prefer: &Option<PreferOptions>) -> QueryBuilder {
+    let mut builder = QueryBuilder::new();
+    let Some(prefer) = prefer else {
+        return builder;
+    };
+    if let Some(representation) = prefer.return_representation {
+        builder = builder.with_return(representation);
+    }
+    if let Some(missing) = prefer.missing {
+        builder = builder.with_missing(missing);
+    }
+    if let Some(resolution) = prefer.resolution {
+        builder = builder.with_resolution(resolution);
+    }
+    builder
+}
+
 /// Extracts column names from query string filters for use as ON CONFLICT target
 ///
 /// Used by PUT requests to automatically determine conflict columns
@@ -588,21 +746,19 @@ fn extract_conflict_columns_from_query(query_string: &str) -> Vec<String> {
     }
 
     let mut columns = Vec::new();
-    for pair in query_string.split('&') {
-        let parts: Vec<&str> = pair.splitn(2, '=').collect();
-        if parts.len() == 2 {
-            let key = parts[0];
-            // Skip reserved keys
-            if !parser::filter::reserved_key(key) && !parser::logic::logic_key(key) {
-                // Extract base column name (before any JSON operators)
-                let column_name = if let Some(arrow_pos) = key.find("->") {
-                    &key[..arrow_pos]
-                } else {
-                    key
-                };
-                if !columns.contains(&column_name.to_string()) {
-                    columns.push(column_name.to_string());
-                }
+    for (key, _) in parse_query_pairs(query_string) {
+        // Skip reserved keys and keys scoped to embedded resources
+        if !parser::filter::reserved_key(&key)
+            && !parser::logic::logic_key(&key)
+            && parser::filter::embedded_key(&key).is_none()
+        {
+            // Extract base column name (before any JSON operators)
+            let column_name = match key.find("->") {
+                Some(arrow_pos) => &key[..arrow_pos],
+                None => &key,
+            };
+            if !columns.iter().any(|c| c == column_name) {
+                columns.push(column_name.to_string());
             }
         }
     }
@@ -615,7 +771,7 @@ fn parse_filters_from_map(
     let mut filters = Vec::new();
 
     for (key, value) in params {
-        if parser::filter::reserved_key(key) {
+        if parser::filter::reserved_key(key) || parser::filter::embedded_key(key).is_some() {
             continue;
         }
 
@@ -639,7 +795,7 @@ fn parse_filters_from_pairs(pairs: &[(String, String)]) -> Result<Vec<LogicCondi
     let mut filters = Vec::new();
 
     for (key, value) in pairs {
-        if parser::filter::reserved_key(key) {
+        if parser::filter::reserved_key(key) || parser::filter::embedded_key(key).is_some() {
             continue;
         }
 
@@ -655,6 +811,40 @@ fn parse_filters_from_pairs(pairs: &[(String, String)]) -> Result<Vec<LogicCondi
     Ok(filters)
 }
 
+/// Adds a key scoped to an embedded resource to the embed tree, creating the
+/// intermediate nodes of `path` as needed.
+fn parse_embedded_param(
+    embedded: &mut BTreeMap<String, EmbedParams>,
+    path: &[String],
+    key: &str,
+    value: &str,
+) -> Result<(), Error> {
+    let Some((first, rest)) = path.split_first() else {
+        return Ok(());
+    };
+    let mut params = embedded.entry(first.clone()).or_default();
+    for name in rest {
+        params = params.embedded.entry(name.clone()).or_default();
+    }
+
+    match key {
+        "order" => params.order = parse_order(value)?,
+        "limit" => params.limit = value.parse::<u64>().ok(),
+        "offset" => params.offset = value.parse::<u64>().ok(),
+        _ if parser::filter::reserved_key(key) => {}
+        _ if parser::logic::logic_key(key) => {
+            let tree = parse_logic(key, value)?;
+            params.filters.push(LogicCondition::Logic(tree));
+        }
+        _ => {
+            let filter = parse_filter(key, value)?;
+            params.filters.push(LogicCondition::Filter(filter));
+        }
+    }
+
+    Ok(())
+}
+
 /// Result of building a filter clause.
 ///
 /// Contains the SQL WHERE clause fragment and associated parameter values.
@@ -705,6 +895,94 @@ mod tests {
         assert_eq!(params.limit, Some(10));
     }
 
+    #[test]
+    fn test_parse_query_string_percent_decoded() {
+        let params = parse_query_string("name=eq.John%20Doe&city=eq.New+York").unwrap();
+        let values: Vec<_> = params
+            .filters
+            .iter()
+            .map(|f| match f {
+                LogicCondition::Filter(filter) => filter.value.clone(),
+                _ => panic!("Expected filter"),
+            })
+            .collect();
+        assert!(values.contains(&FilterValue::Single("John Doe".to_string())));
+        assert!(values.contains(&FilterValue::Single("New York".to_string())));
+    }
+
+    #[test]
+    fn test_parse_query_string_embedded_params() {
+        let params = parse_query_string(
+            "select=*,posts(*,comments(*))&status=eq.active&posts.status=eq.published&posts.order=created_at.desc&posts.limit=5&posts.offset=10&posts.comments.or=(flagged.is.true,score.lt.0)",
+        )
+        .unwrap();
+
+        assert_eq!(params.filters.len(), 1);
+        let posts = &params.embedded["posts"];
+        assert_eq!(posts.filters.len(), 1);
+        match &posts.filters[0] {
+            LogicCondition::Filter(filter) => {
+                assert_eq!(filter.field.name, "status");
+                assert_eq!(filter.value, FilterValue::Single("published".to_string()));
+            }
+            _ => panic!("Expected filter"),
+        }
+        assert_eq!(posts.order[0].field.name, "created_at");
+        assert_eq!(posts.order[0].direction, Direction::Desc);
+        assert_eq!(posts.limit, Some(5));
+        assert_eq!(posts.offset, Some(10));
+
+        let comments = &posts.embedded["comments"];
+        assert!(
+            matches!(&comments.filters[0], LogicCondition::Logic(tree) if tree.operator == LogicOperator::Or)
+        );
+    }
+
+    #[test]
+    fn test_parse_params_map_embedded_params() {
+        let mut map = std::collections::HashMap::new();
+        map.insert("select".to_string(), "id,posts(id)".to_string());
+        map.insert("posts.limit".to_string(), "3".to_string());
+        let params = parse_params(&map).unwrap();
+        assert!(params.filters.is_empty());
+        assert_eq!(params.embedded["posts"].limit, Some(3));
+    }
+
+    #[test]
+    fn test_parse_query_string_encoded_list_and_logic() {
+        let params = parse_query_string(
+            "status=in.%28active%2C%22on%2Chold%22%29&or=%28id.eq.1%2Cid.eq.2%29",
+        )
+        .unwrap();
+        assert_eq!(params.filters.len(), 2);
+        match &params.filters[0] {
+            LogicCondition::Filter(filter) => assert_eq!(
+                filter.value,
+                FilterValue::List(vec!["active".to_string(), "on,hold".to_string()])
+            ),
+            _ => panic!("Expected filter"),
+        }
+        match &params.filters[1] {
+            LogicCondition::Logic(tree) => assert_eq!(tree.conditions.len(), 2),
+            _ => panic!("Expected logic tree"),
+        }
+    }
+
+    #[test]
+    fn test_parse_percent_decodes_request_query() {
+        let op = parse(
+            "GET",
+            "users",
+            "select=id%2Cname&name=eq.Jos%C3%A9",
+            None,
+            None,
+        )
+        .unwrap();
+        let sql = operation_to_sql("users", &op).unwrap();
+        assert!(sql.query.contains(r#""id", "name""#));
+        assert_eq!(sql.params, vec![serde_json::json!("José")]);
+    }
+
     #[test]
     fn test_to_sql_simple() {
         let params = ParsedParams::new()
@@ -949,11 +1227,191 @@ mod tests {
         let op = parse("GET", "users", "limit=10&offset=0", None, Some(&headers)).unwrap();
 
         match op {
-            Operation::Select(_, Some(prefer)) => {
+            Operation::Select(_, Some(ref prefer)) => {
                 assert_eq!(prefer.count, Some(Count::Exact));
             }
             _ => panic!("Expected Select with count"),
         }
+        let sql = operation_to_sql("events", &op).unwrap();
+        assert_eq!(sql.count.unwrap().strategy, Count::Exact);
+    }
+
+    #[test]
+    fn test_prefer_return_drives_returning() {
+        use std::collections::HashMap;
+        let prefer = |value: &str| HashMap::from([("Prefer".to_string(), value.to_string())]);
+        let body = r#"{"name": "Alice"}"#;
+
+        let op = parse(
+            "POST",
+            "users",
+            "",
+            Some(body),
+            Some(&prefer("return=representation")),
+        )
+        .unwrap();
+        let result = operation_to_sql("users", &op).unwrap();
+        assert!(result.query.ends_with(" RETURNING *"));
+        assert_eq!(result.preference_applied, vec!["return=representation"]);
+
+        let op = parse(
+            "PATCH",
+            "users",
+            "id=eq.1&select=id,name",
+            Some(body),
+            Some(&prefer("return=minimal")),
+        )
+        .unwrap();
+        let result = operation_to_sql("users", &op).unwrap();
+        assert!(!result.query.contains("RETURNING"));
+        assert_eq!(result.preference_applied, vec!["return=minimal"]);
+
+        // Without a schema cache the primary key is unknown
+        let op = parse(
+            "DELETE",
+            "users",
+            "id=eq.1",
+            None,
+            Some(&prefer("return=headers-only")),
+        )
+        .unwrap();
+        let result = operation_to_sql("users", &op).unwrap();
+        assert!(!result.query.contains("RETURNING"));
+
+        // No preference keeps the select list, and reports nothing
+        let op = parse("DELETE", "users", "id=eq.1&select=id", None, None).unwrap();
+        let result = operation_to_sql("users", &op).unwrap();
+        assert!(result.query.ends_with(r#" RETURNING "id""#));
+        assert!(result.preference_applied.is_empty());
+    }
+
+    #[test]
+    fn test_prefer_missing_default_in_bulk_insert() {
+        use std::collections::HashMap;
+        let headers = HashMap::from([("Prefer".to_string(), "missing=default".to_string())]);
+        let body = r#"[{"name": "Alice"}, {"name": "Bob", "email": "bob@example.com"}]"#;
+
+        let op = parse("POST", "users", "", Some(body), Some(&headers)).unwrap();
+        let result = operation_to_sql("users", &op).unwrap();
+        assert_eq!(
+            result.query,
+            r#"INSERT INTO "public"."users" ("email", "name") VALUES (DEFAULT, $1), ($2, $3)"#
+        );
+        assert_eq!(result.preference_applied, vec!["missing=default"]);
+
+        // Without the preference absent keys are NULL
+        let op = parse("POST", "users", "", Some(body), None).unwrap();
+        let result = operation_to_sql("users", &op).unwrap();
+        assert!(result.query.ends_with("VALUES ($1, $2), ($3, $4)"));
+        assert_eq!(result.params[0], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_prefer_resolution_upsert() {
+        use std::collections::HashMap;
+        let headers = HashMap::from([(
+            "Prefer".to_string(),
+            "resolution=merge-duplicates".to_string(),
+        )]);
+        let body = r#"{"user_id": 123, "theme": "dark"}"#;
+
+        let op = parse(
+            "POST",
+            "preferences",
+            "on_conflict=user_id",
+            Some(body),
+            Some(&headers),
+        )
+        .unwrap();
+        let result = operation_to_sql("preferences", &op).unwrap();
+        assert!(result
+            .query
+            .ends_with(r#"ON CONFLICT ("user_id") DO UPDATE SET "theme" = EXCLUDED."theme""#));
+        assert_eq!(
+            result.preference_applied,
+            vec!["resolution=merge-duplicates"]
+        );
+
+        // Without the preference, on_conflict= alone ignores duplicates
+        let op = parse(
+            "POST",
+            "preferences",
+            "on_conflict=user_id",
+            Some(body),
+            None,
+        )
+        .unwrap();
+        let result = operation_to_sql("preferences", &op).unwrap();
+        assert!(result
+            .query
+            .ends_with(r#"ON CONFLICT ("user_id") DO NOTHING"#));
+    }
+
+    #[test]
+    fn test_range_header_pagination() {
+        use std::collections::HashMap;
+        let headers = HashMap::from([
+            ("Range".to_string(), "20-29".to_string()),
+            ("Range-Unit".to_string(), "items".to_string()),
+        ]);
+
+        let op = parse("GET", "users", "order=id", None, Some(&headers)).unwrap();
+        match &op {
+            Operation::Select(params, _) => {
+                assert_eq!(params.offset, Some(20));
+                assert_eq!(params.limit, Some(10));
+            }
+            _ => panic!("Expected Select"),
+        }
+
+        // The query string's limit wins over the header
+        let op = parse("GET", "users", "limit=5", None, Some(&headers)).unwrap();
+        match &op {
+            Operation::Select(params, _) => {
+                assert_eq!(params.offset, Some(20));
+                assert_eq!(params.limit, Some(5));
+            }
+            _ => panic!("Expected Select"),
+        }
+
+        let headers = HashMap::from([("range".to_string(), "9-0".to_string())]);
+        assert_eq!(
+            parse("GET", "users", "", None, Some(&headers)),
+            Err(Error::Parse(ParseError::InvalidRange("9-0".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_accept_object_requests_singular_response() {
+        use std::collections::HashMap;
+        let headers = HashMap::from([(
+            "accept".to_string(),
+            "application/vnd.pgrst.object+json; nulls=stripped".to_string(),
+        )]);
+
+        let op = parse("GET", "users", "id=eq.1", None, Some(&headers)).unwrap();
+        match &op {
+            Operation::Select(_, Some(prefer)) => {
+                assert_eq!(prefer.plurality, Some(Plurality::Singular));
+            }
+            _ => panic!("Expected Select with singular plurality"),
+        }
+
+        let result = operation_to_sql("users", &op).unwrap();
+        assert_eq!(result.wrapped, Some(Plurality::Singular));
+        assert!(result
+            .query
+            .contains(r#"coalesce(json_agg("_postgrest_t")->0, 'null') AS "body""#));
+        assert_eq!(
+            result.check_page_total(2),
+            Err(SqlError::SingularRowCount(2))
+        );
+        assert!(result.check_page_total(1).is_ok());
+
+        let headers = HashMap::from([("Accept".to_string(), "application/json".to_string())]);
+        let op = parse("GET", "users", "id=eq.1", None, Some(&headers)).unwrap();
+        assert!(matches!(op, Operation::Select(_, None)));
+        assert_eq!(operation_to_sql("users", &op).unwrap().wrapped, None);
     }
 
     #[test]
@@ -1248,6 +1706,121 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_rpc_get_args_from_schema_cache() {
+        let mut cache = SchemaCache::new();
+        cache.add_function(
+            Function::new("public", "search_products")
+                .with_params(vec![FunctionParam::new("term", "text")])
+                .with_volatility(Volatility::Stable),
+        );
+        let query = "term=Mouse&category=eq.Electronics";
+
+        let op = parse_with_schema_cache("GET", "rpc/search_products", query, None, None, &cache)
+            .unwrap();
+        let result = operation_to_sql("rpc/search_products", &op).unwrap();
+        assert_eq!(
+            result.query,
+            r#"SELECT * FROM "public"."search_products"("term" := $1) WHERE "category" = $2"#
+        );
+
+        // POST takes arguments from the body only; a query key naming a
+        // parameter filters the results
+        let body = r#"{"term": "Mouse"}"#;
+        let op = parse_with_schema_cache(
+            "POST",
+            "rpc/search_products",
+            "term=eq.Lamp",
+            Some(body),
+            None,
+            &cache,
+        )
+        .unwrap();
+        let result = operation_to_sql("rpc/search_products", &op).unwrap();
+        assert_eq!(
+            result.query,
+            r#"SELECT * FROM "public"."search_products"("term" := $1) WHERE "term" = $2"#
+        );
+        assert_eq!(
+            result.params,
+            vec![serde_json::json!("Mouse"), serde_json::json!("Lamp")]
+        );
+
+        // Unknown functions keep treating every key as a filter
+        let result = parse_with_schema_cache("GET", "rpc/other", query, None, None, &cache);
+        assert!(result.is_err());
+
+        // Tables are parsed as usual
+        let op = parse_with_schema_cache("GET", "products", "id=eq.1", None, None, &cache).unwrap();
+        assert!(matches!(op, Operation::Select(_, _)));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_rpc_get_rejects_volatile_function() {
+        let mut cache = SchemaCache::new();
+        cache.add_function(
+            Function::new("public", "place_order")
+                .with_params(vec![FunctionParam::new("customer", "integer")]),
+        );
+
+        let result =
+            parse_with_schema_cache("GET", "rpc/place_order", "customer=1", None, None, &cache);
+        assert_eq!(
+            result,
+            Err(Error::Parse(ParseError::VolatileFunctionGet(
+                "place_order".to_string()
+            )))
+        );
+
+        let body = r#"{"customer": 1}"#;
+        let op = parse_with_schema_cache("POST", "rpc/place_order", "", Some(body), None, &cache);
+        assert!(op.is_ok());
+    }
+
+    #[test]
+    fn test_rpc_single_object_and_raw_bodies() {
+        use std::collections::HashMap;
+
+        // Prefer: params=single-object passes the body as one argument
+        let headers = HashMap::from([("Prefer".to_string(), "params=single-object".to_string())]);
+        let body = r#"{"user_id": 1, "tags": ["a"]}"#;
+        let op = parse("POST", "rpc/update_profile", "", Some(body), Some(&headers)).unwrap();
+        match &op {
+            Operation::Rpc(params, _) => {
+                assert!(params.args.is_empty());
+                assert_eq!(
+                    params.body,
+                    RpcBody::SingleObject(serde_json::json!({"user_id": 1, "tags": ["a"]}))
+                );
+            }
+            _ => panic!("Expected RPC operation"),
+        }
+        let result = operation_to_sql("rpc/update_profile", &op).unwrap();
+        assert_eq!(
+            result.query,
+            r#"SELECT * FROM "public"."update_profile"($1)"#
+        );
+        assert_eq!(result.preference_applied, vec!["params=single-object"]);
+
+        // Raw text bodies are passed as-is, and need not be JSON
+        let headers = HashMap::from([(
+            "Content-Type".to_string(),
+            "text/plain; charset=utf-8".to_string(),
+        )]);
+        let op = parse("POST", "rpc/echo", "", Some("hello, world"), Some(&headers)).unwrap();
+        match op {
+            Operation::Rpc(params, _) => {
+                assert_eq!(
+                    params.body,
+                    RpcBody::Raw(RawMediaType::Text, "hello, world".to_string())
+                );
+            }
+            _ => panic!("Expected RPC operation"),
+        }
+    }
+
     // Phase 5: Resource Embedding Tests
 
     #[test]
@@ -1424,6 +1997,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_put_conflict_columns_are_decoded() {
+        let body = r#"{"user id": 1, "name": "Alice"}"#;
+        let op = parse("PUT", "users", "user%20id=eq.1", Some(body), None).unwrap();
+        match op {
+            Operation::Insert(params, _) => {
+                let on_conflict = params.on_conflict.unwrap();
+                assert_eq!(on_conflict.columns, vec!["user id"]);
+            }
+            _ => panic!("Expected Insert operation"),
+        }
+    }
+
+    #[test]
+    fn test_put_conflict_columns_skip_embedded_keys() {
+        let body = r#"{"id": 1, "name": "Alice"}"#;
+        let op = parse(
+            "PUT",
+            "users",
+            "id=eq.1&posts.status=eq.draft",
+            Some(body),
+            None,
+        )
+        .unwrap();
+        match op {
+            Operation::Insert(params, _) => {
+                assert_eq!(params.on_conflict.unwrap().columns, vec!["id"]);
+            }
+            _ => panic!("Expected Insert operation"),
+        }
+    }
+
     #[test]
     fn test_put_to_sql() {
         // Real-world: Verify PUT generates proper upsert SQL
@@ -1708,11 +2313,13 @@ mod tests {
         )
         .unwrap();
         match op {
-            Operation::Select(_, Some(prefer)) => {
+            Operation::Select(_, Some(ref prefer)) => {
                 assert_eq!(prefer.count, Some(Count::Exact));
             }
             _ => panic!("Expected Select with count"),
         }
+        let sql = operation_to_sql("events", &op).unwrap();
+        assert_eq!(sql.count.unwrap().strategy, Count::Exact);
     }
 
     // Resource Embedding Tests (PostgREST select with relations)
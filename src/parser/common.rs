@@ -175,6 +175,61 @@ pub fn parse_field_fallback(field_str: &str) -> Result<Field, ParseError> {
     }
 }
 
+/// Splits a list body on commas that are not inside double quotes, unquoting each item.
+pub(crate) fn split_quoted_list(input: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in input.chars() {
+        match c {
+            _ if escaped => {
+                escaped = false;
+                current.push(c);
+            }
+            '\\' if in_quotes => {
+                escaped = true;
+                current.push(c);
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ',' if !in_quotes => {
+                items.push(unquote(current.trim()));
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    items.push(unquote(current.trim()));
+
+    items
+}
+
+/// Strips PostgREST double quotes from a value, resolving `\"` and `\\` escapes.
+///
+/// Values that are not fully wrapped in double quotes are returned unchanged.
+pub(crate) fn unquote(value: &str) -> String {
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return value.to_string();
+    }
+
+    let mut result = String::with_capacity(value.len() - 2);
+    let mut chars = value[1..value.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                result.push(next);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -283,4 +338,17 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().1, "test string");
     }
+
+    #[test]
+    fn test_split_quoted_list() {
+        let items = split_quoted_list(r#"a, "b,c" ,"say \"hi\"""#);
+        assert_eq!(items, vec!["a", "b,c", r#"say "hi""#]);
+    }
+
+    #[test]
+    fn test_unquote_leaves_unquoted_values() {
+        assert_eq!(unquote("plain"), "plain");
+        assert_eq!(unquote("\"half"), "\"half");
+        assert_eq!(unquote("\"a.b\""), "a.b");
+    }
 }
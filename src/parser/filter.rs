@@ -1,4 +1,5 @@
-use super::common::{field, parse_field_fallback};
+use super::common::{field, parse_field_fallback, split_quoted_list};
+use super::logic::logic_key;
 use crate::ast::{Field, Filter, FilterOperator, FilterValue, Quantifier};
 use crate::error::ParseError;
 
@@ -267,8 +268,7 @@ fn parse_value(
 fn parse_list_value(value_str: &str, open: char, close: char) -> Result<FilterValue, ParseError> {
     if value_str.starts_with(open) && value_str.ends_with(close) {
         let inner = &value_str[1..value_str.len() - 1];
-        let items: Vec<String> = inner.split(',').map(|s| s.trim().to_string()).collect();
-        Ok(FilterValue::List(items))
+        Ok(FilterValue::List(split_quoted_list(inner)))
     } else {
         Err(ParseError::ExpectedListFormat(format!(
             "expected list with {} and {}",
@@ -284,6 +284,44 @@ pub fn reserved_key(key: &str) -> bool {
     )
 }
 
+/// Splits a query key scoped to an embedded resource into the embed path and the
+/// key that applies inside it.
+///
+/// `posts.status` yields `(["posts"], "status")`, `posts.comments.order` yields
+/// `(["posts", "comments"], "order")` and `posts.not.or` yields `(["posts"], "not.or")`.
+/// Dots after a JSON operator (`data->a.b`) are not path separators. Returns `None`
+/// for keys that target the root resource.
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::parser::embedded_key;
+///
+/// let (path, key) = embedded_key("posts.order").unwrap();
+/// assert_eq!(path, vec!["posts"]);
+/// assert_eq!(key, "order");
+/// assert!(embedded_key("status").is_none());
+/// ```
+pub fn embedded_key(key: &str) -> Option<(Vec<String>, String)> {
+    if logic_key(key) {
+        return None;
+    }
+
+    let (head, json_path) = key.split_at(key.find("->").unwrap_or(key.len()));
+    let mut segments: Vec<&str> = head.split('.').collect();
+    if segments.len() < 2 || segments.iter().any(|segment| segment.is_empty()) {
+        return None;
+    }
+
+    let mut target = format!("{}{}", segments.pop()?, json_path);
+    if segments.len() > 1 && segments.last()?.eq_ignore_ascii_case("not") && logic_key(&target) {
+        segments.pop();
+        target = format!("not.{}", target);
+    }
+
+    Some((segments.into_iter().map(String::from).collect(), target))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -380,6 +418,38 @@ mod tests {
         assert!(!reserved_key("id"));
     }
 
+    #[test]
+    fn test_embedded_key() {
+        assert_eq!(
+            embedded_key("posts.status"),
+            Some((vec!["posts".to_string()], "status".to_string()))
+        );
+        assert_eq!(
+            embedded_key("posts.comments.limit"),
+            Some((
+                vec!["posts".to_string(), "comments".to_string()],
+                "limit".to_string()
+            ))
+        );
+        assert_eq!(
+            embedded_key("posts.not.or"),
+            Some((vec!["posts".to_string()], "not.or".to_string()))
+        );
+        assert_eq!(
+            embedded_key("posts.data->a.b"),
+            Some((vec!["posts".to_string()], "data->a.b".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_embedded_key_root_keys() {
+        assert!(embedded_key("status").is_none());
+        assert!(embedded_key("not.or").is_none());
+        assert!(embedded_key("data->a.b").is_none());
+        assert!(embedded_key(".status").is_none());
+        assert!(embedded_key("posts.").is_none());
+    }
+
     #[test]
     fn test_parse_filter_with_whitespace_in_list() {
         let result = parse_filter("status", "in.(active, pending, closed)");
@@ -397,6 +467,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_filter_in_with_quoted_values() {
+        let filter = parse_filter("name", r#"in.("Doe, John",Smith,"a\"b")"#).unwrap();
+        assert_eq!(
+            filter.value,
+            FilterValue::List(vec![
+                "Doe, John".to_string(),
+                "Smith".to_string(),
+                "a\"b".to_string()
+            ])
+        );
+    }
+
     #[test]
     fn test_parse_filter_comparison_operators() {
         // GT operator
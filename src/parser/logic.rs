@@ -1,4 +1,7 @@
-use super::{common::parse_field_fallback, filter::parse_filter};
+use super::{
+    common::{parse_field_fallback, unquote},
+    filter::parse_filter,
+};
 use crate::ast::{
     Field, Filter, FilterOperator, FilterValue, LogicCondition, LogicOperator, LogicTree,
 };
@@ -69,9 +72,26 @@ fn split_at_top_level_commas(str: &str) -> Result<Vec<String>, ParseError> {
     let mut parts = Vec::new();
     let mut current = String::new();
     let mut depth = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
 
     for c in str.chars() {
         match c {
+            _ if escaped => {
+                escaped = false;
+                current.push(c);
+            }
+            '\\' if in_quotes => {
+                escaped = true;
+                current.push(c);
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            _ if in_quotes => {
+                current.push(c);
+            }
             '(' => {
                 depth += 1;
                 current.push(c);
@@ -189,50 +209,28 @@ fn parse_equals_notation(str: &str) -> Result<LogicCondition, ParseError> {
 }
 
 fn parse_dot_notation(str: &str) -> Result<LogicCondition, ParseError> {
-    let parts: Vec<&str> = str.split('.').collect();
-
-    if parts.len() == 3 {
-        let field_str = parts[0];
-        let operator_str = parts[1];
-        let value_str = parts[2];
-
-        let operator = parse_filter_operator(operator_str)?;
-        let value = FilterValue::Single(value_str.to_string());
-
-        let field = parse_filter_field(field_str)?;
-
-        Ok(LogicCondition::Filter(Filter {
-            field,
-            operator,
-            value,
-            quantifier: None,
-            language: None,
-            negated: false,
-        }))
-    } else if parts.len() == 4 && parts[1] == "not" {
-        let field_str = parts[0];
-        let operator_str = parts[2];
-        let value_str = parts[3];
-
-        let operator = parse_filter_operator(operator_str)?;
-        let value = FilterValue::Single(value_str.to_string());
-
-        let field = parse_filter_field(field_str)?;
-
-        Ok(LogicCondition::Filter(Filter {
-            field,
-            operator,
-            value,
-            quantifier: None,
-            language: None,
-            negated: true,
-        }))
-    } else {
-        Err(ParseError::InvalidFilterFormat(format!(
-            "invalid dot notation: {}",
-            str
-        )))
-    }
+    let invalid = || ParseError::InvalidFilterFormat(format!("invalid dot notation: {}", str));
+
+    let (field_str, rest) = str.split_once('.').ok_or_else(invalid)?;
+    let (negated, rest) = match rest.strip_prefix("not.") {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let (operator_str, value_str) = rest.split_once('.').ok_or_else(invalid)?;
+
+    let operator = parse_filter_operator(operator_str)?;
+    let value = FilterValue::Single(unquote(value_str));
+
+    let field = parse_filter_field(field_str)?;
+
+    Ok(LogicCondition::Filter(Filter {
+        field,
+        operator,
+        value,
+        quantifier: None,
+        language: None,
+        negated,
+    }))
 }
 
 fn parse_filter_operator(op_str: &str) -> Result<FilterOperator, ParseError> {
@@ -377,4 +375,47 @@ mod tests {
         assert!(result.is_ok());
         assert!(matches!(result.unwrap(), LogicCondition::Filter(_)));
     }
+
+    #[test]
+    fn test_parse_dot_notation_negated() {
+        let result = parse_dot_notation("status.not.eq.active").unwrap();
+        match result {
+            LogicCondition::Filter(filter) => {
+                assert!(filter.negated);
+                assert_eq!(filter.value, FilterValue::Single("active".to_string()));
+            }
+            _ => panic!("Expected filter"),
+        }
+    }
+
+    #[test]
+    fn test_parse_dot_notation_value_with_dots() {
+        match parse_dot_notation("price.eq.9.99").unwrap() {
+            LogicCondition::Filter(filter) => {
+                assert_eq!(filter.value, FilterValue::Single("9.99".to_string()));
+            }
+            _ => panic!("Expected filter"),
+        }
+    }
+
+    #[test]
+    fn test_parse_logic_quoted_value_with_reserved_characters() {
+        let tree = parse_logic("or", r#"(name.eq."Doe, John (Jr.)",id.eq.1)"#).unwrap();
+        assert_eq!(tree.conditions.len(), 2);
+        match &tree.conditions[0] {
+            LogicCondition::Filter(filter) => {
+                assert_eq!(
+                    filter.value,
+                    FilterValue::Single("Doe, John (Jr.)".to_string())
+                );
+            }
+            _ => panic!("Expected filter"),
+        }
+    }
+
+    #[test]
+    fn test_split_at_top_level_commas_ignores_quoted_commas() {
+        let parts = split_at_top_level_commas(r#"a.eq."x,y",b.eq."(",c.eq.1"#).unwrap();
+        assert_eq!(parts, vec![r#"a.eq."x,y""#, r#"b.eq."(""#, "c.eq.1"]);
+    }
 }
@@ -5,17 +5,21 @@ pub mod logic;
 pub mod mutation;
 pub mod order;
 pub mod prefer;
+pub mod query_string;
+pub mod range;
 pub mod rpc;
 pub mod schema;
 pub mod select;
 
 pub use body::{parse_json_body, validate_insert_body, validate_update_body};
 pub use common::{field, identifier, json_path, json_path_segment, type_cast};
-pub use filter::{parse_filter, reserved_key};
+pub use filter::{embedded_key, parse_filter, reserved_key};
 pub use logic::{logic_key, parse_logic};
 pub use mutation::{parse_delete_params, parse_insert_params, parse_update_params};
 pub use order::{parse_order, parse_order_term};
 pub use prefer::parse_prefer_header;
-pub use rpc::parse_rpc_params;
+pub use query_string::{parse_query_pairs, percent_decode};
+pub use range::parse_range_header;
+pub use rpc::{parse_rpc_params, parse_rpc_params_with_functions};
 pub use schema::{get_profile_header, parse_qualified_table, resolve_schema};
 pub use select::parse_select;
@@ -1,7 +1,8 @@
 use crate::ast::{ConflictAction, DeleteParams, InsertParams, OnConflict, UpdateParams};
 use crate::error::{Error, ParseError};
 use crate::parser::{
-    parse_json_body, parse_order, parse_select, validate_insert_body, validate_update_body,
+    parse_json_body, parse_order, parse_query_pairs, parse_select, validate_insert_body,
+    validate_update_body,
 };
 use std::collections::HashMap;
 
@@ -190,17 +191,7 @@ pub fn parse_delete_params(query_string: &str) -> Result<DeleteParams, Error> {
 }
 
 fn parse_query_params(query_string: &str) -> HashMap<String, String> {
-    query_string
-        .split('&')
-        .filter_map(|pair| {
-            let parts: Vec<&str> = pair.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                Some((parts[0].to_string(), parts[1].to_string()))
-            } else {
-                None
-            }
-        })
-        .collect()
+    parse_query_pairs(query_string).into_iter().collect()
 }
 
 fn is_reserved_key(key: &str) -> bool {
@@ -333,6 +324,19 @@ mod tests {
         assert_eq!(params.order.len(), 1);
     }
 
+    #[test]
+    fn test_parse_update_params_percent_decoded() {
+        let body = r#"{"status": "active"}"#;
+        let params = parse_update_params("name=eq.John%20Doe&order=created_at.desc", body).unwrap();
+        match &params.filters[0] {
+            crate::ast::LogicCondition::Filter(filter) => assert_eq!(
+                filter.value,
+                crate::ast::FilterValue::Single("John Doe".to_string())
+            ),
+            _ => panic!("Expected filter"),
+        }
+    }
+
     #[test]
     fn test_parse_delete_params_simple() {
         let result = parse_delete_params("id=eq.123");
@@ -19,6 +19,7 @@ fn is_nulls_option(s: &str) -> bool {
 /// - With nulls handling: `column.desc.nullsfirst` or `column.asc.nullslast`
 /// - JSON fields: `data->created_at.desc`
 /// - Type casts: `price::numeric.desc`
+/// - Embedded to-one resources: `clients(name).desc.nullslast`
 ///
 /// # Examples
 ///
@@ -44,6 +45,10 @@ fn is_nulls_option(s: &str) -> bool {
 /// // Default direction (ascending)
 /// let terms = parse_order("id").unwrap();
 /// assert_eq!(terms.len(), 1);
+///
+/// // Column of an embedded resource
+/// let terms = parse_order("clients(name).desc").unwrap();
+/// assert_eq!(terms[0].relation, Some("clients".to_string()));
 /// ```
 ///
 /// # Errors
@@ -77,6 +82,10 @@ pub fn parse_order(order_str: &str) -> Result<Vec<OrderTerm>, ParseError> {
 /// assert_eq!(term.direction, Direction::Desc);
 /// ```
 pub fn parse_order_term(term_str: &str) -> Result<OrderTerm, ParseError> {
+    if term_str.contains('(') {
+        return parse_related_order_term(term_str);
+    }
+
     let parts: Vec<&str> = term_str.split('.').collect();
 
     if parts.is_empty() || parts[0].is_empty() {
@@ -98,6 +107,41 @@ pub fn parse_order_term(term_str: &str) -> Result<OrderTerm, ParseError> {
     Ok(term)
 }
 
+/// Parses `relation(field).options`, ordering by a column of an embedded resource
+fn parse_related_order_term(term_str: &str) -> Result<OrderTerm, ParseError> {
+    let invalid = || ParseError::InvalidOrderOptions(term_str.to_string());
+
+    let (relation, rest) = term_str.split_once('(').ok_or_else(invalid)?;
+    let (field_str, options) = rest.split_once(')').ok_or_else(invalid)?;
+    let relation = relation.trim();
+    let field_str = field_str.trim();
+    if relation.is_empty() || field_str.is_empty() || field_str.contains('(') {
+        return Err(invalid());
+    }
+
+    let option_parts: Vec<String> = match options {
+        "" => Vec::new(),
+        _ => options
+            .strip_prefix('.')
+            .ok_or_else(invalid)?
+            .split('.')
+            .map(str::to_string)
+            .collect(),
+    };
+
+    let field = parse_order_field(field_str)?;
+    let (direction, nulls) = parse_options(&option_parts)?;
+
+    let mut term = OrderTerm::new(field)
+        .with_direction(direction)
+        .with_relation(relation);
+    if let Some(n) = nulls {
+        term = term.with_nulls(n);
+    }
+
+    Ok(term)
+}
+
 fn split_field_and_options(parts: &[&str]) -> (Vec<String>, Vec<String>) {
     if parts.is_empty() {
         return (Vec::new(), Vec::new());
@@ -208,6 +252,37 @@ mod tests {
         assert!(matches!(result, Err(ParseError::InvalidOrderOptions(_))));
     }
 
+    #[test]
+    fn test_parse_order_related_resource() {
+        let terms = parse_order("clients(name).desc.nullslast,id").unwrap();
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].relation, Some("clients".to_string()));
+        assert_eq!(terms[0].field.name, "name");
+        assert_eq!(terms[0].direction, Direction::Desc);
+        assert_eq!(terms[0].nulls, Some(Nulls::Last));
+        assert_eq!(terms[1].relation, None);
+
+        let terms = parse_order("clients(data->>rank::int)").unwrap();
+        assert_eq!(terms[0].direction, Direction::Asc);
+        assert_eq!(terms[0].field.cast, Some("int".to_string()));
+    }
+
+    #[test]
+    fn test_parse_order_related_resource_invalid() {
+        for order in [
+            "(name).desc",
+            "clients().desc",
+            "clients(name",
+            "clients(name)desc",
+        ] {
+            assert!(
+                matches!(parse_order(order), Err(ParseError::InvalidOrderOptions(_))),
+                "{order}"
+            );
+        }
+        assert!(parse_order("clients(name).sideways").is_err());
+    }
+
     #[test]
     fn test_parse_order_with_cast() {
         let result = parse_order("price::numeric.desc");
@@ -1,4 +1,6 @@
-use crate::ast::{Count, Missing, Plurality, PreferOptions, Resolution, ReturnRepresentation};
+use crate::ast::{
+    Count, Missing, ParamsMode, Plurality, PreferOptions, Resolution, ReturnRepresentation,
+};
 use crate::error::Error;
 use nom::{branch::alt, bytes::complete::tag, combinator::map, sequence::preceded, IResult};
 
@@ -59,6 +61,14 @@ fn parse_missing(input: &str) -> IResult<&str, Missing> {
     )(input)
 }
 
+/// Parses "params=single-object"
+fn parse_params(input: &str) -> IResult<&str, ParamsMode> {
+    preceded(
+        tag("params="),
+        map(tag("single-object"), |_| ParamsMode::SingleObject),
+    )(input)
+}
+
 /// Parses a full Prefer header value
 ///
 /// Format: "option1=value1, option2=value2, ..."
@@ -91,6 +101,8 @@ pub fn parse_prefer_header(input: &str) -> Result<PreferOptions, Error> {
             options.plurality = Some(plur);
         } else if let Ok((_, miss)) = parse_missing(part) {
             options.missing = Some(miss);
+        } else if let Ok((_, params)) = parse_params(part) {
+            options.params = Some(params);
         } else {
             // Unknown preference - skip it (PostgREST behavior)
             continue;
@@ -160,6 +172,15 @@ mod tests {
         assert_eq!(parse_missing("missing=null").unwrap().1, Missing::Null);
     }
 
+    #[test]
+    fn test_parse_params() {
+        assert_eq!(
+            parse_params("params=single-object").unwrap().1,
+            ParamsMode::SingleObject
+        );
+        assert!(parse_params("params=multiple-objects").is_err());
+    }
+
     #[test]
     fn test_parse_prefer_header_single() {
         let opts = parse_prefer_header("return=representation").unwrap();
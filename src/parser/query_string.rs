@@ -0,0 +1,158 @@
+/// Splits a URL query string into percent-decoded key/value pairs.
+///
+/// Follows the `application/x-www-form-urlencoded` rules browsers use: pairs are
+/// separated by `&`, the first `=` separates key from value, `+` decodes to a space
+/// and `%XX` escapes are decoded in both keys and values. Malformed escapes are kept
+/// verbatim. A leading `?` is ignored, and empty segments or segments without `=`
+/// carry no PostgREST meaning and are skipped.
+///
+/// Decoding happens before any PostgREST grammar is applied, so an encoded comma
+/// (`%2C`) or parenthesis (`%28`) behaves exactly like its literal counterpart. Values
+/// containing reserved characters must be double-quoted, as in PostgREST.
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::parser::parse_query_pairs;
+///
+/// let pairs = parse_query_pairs("name=eq.John%20Doe&city=eq.New+York");
+/// assert_eq!(pairs[0], ("name".to_string(), "eq.John Doe".to_string()));
+/// assert_eq!(pairs[1], ("city".to_string(), "eq.New York".to_string()));
+/// ```
+pub fn parse_query_pairs(query_string: &str) -> Vec<(String, String)> {
+    let query_string = query_string.strip_prefix('?').unwrap_or(query_string);
+
+    query_string
+        .split('&')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            Some((percent_decode(key), percent_decode(value)))
+        })
+        .collect()
+}
+
+/// Decodes a single `application/x-www-form-urlencoded` component.
+///
+/// `+` becomes a space and `%XX` sequences are decoded as UTF-8 bytes. Invalid
+/// escapes are left untouched and invalid UTF-8 is replaced with `U+FFFD`.
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::parser::percent_decode;
+///
+/// assert_eq!(percent_decode("in.%28a%2Cb%29"), "in.(a,b)");
+/// assert_eq!(percent_decode("100%"), "100%");
+/// ```
+pub fn percent_decode(input: &str) -> String {
+    if !input.contains(['%', '+']) {
+        return input.to_string();
+    }
+
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            b'%' if i + 2 < bytes.len() => {
+                match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push(high << 4 | low);
+                        i += 3;
+                    }
+                    _ => {
+                        decoded.push(b'%');
+                        i += 1;
+                    }
+                }
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_pairs_basic() {
+        let pairs = parse_query_pairs("select=id,name&age=gte.18");
+        assert_eq!(
+            pairs,
+            vec![
+                ("select".to_string(), "id,name".to_string()),
+                ("age".to_string(), "gte.18".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_query_pairs_decodes_keys_and_values() {
+        let pairs = parse_query_pairs("first%20name=eq.John%20Doe&city=eq.New+York");
+        assert_eq!(pairs[0].0, "first name");
+        assert_eq!(pairs[0].1, "eq.John Doe");
+        assert_eq!(pairs[1].1, "eq.New York");
+    }
+
+    #[test]
+    fn test_parse_query_pairs_keeps_encoded_separators_in_value() {
+        let pairs = parse_query_pairs("note=eq.a%26b%3Dc");
+        assert_eq!(pairs, vec![("note".to_string(), "eq.a&b=c".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_query_pairs_skips_empty_and_valueless_segments() {
+        let pairs = parse_query_pairs("?&flag&id=eq.1&");
+        assert_eq!(pairs, vec![("id".to_string(), "eq.1".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_query_pairs_empty() {
+        assert!(parse_query_pairs("").is_empty());
+    }
+
+    #[test]
+    fn test_percent_decode_reserved_characters() {
+        assert_eq!(
+            percent_decode("or=%28id.eq.1%2Cid.eq.2%29"),
+            "or=(id.eq.1,id.eq.2)"
+        );
+    }
+
+    #[test]
+    fn test_percent_decode_utf8() {
+        assert_eq!(percent_decode("Jos%C3%A9"), "José");
+    }
+
+    #[test]
+    fn test_percent_decode_malformed_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(percent_decode("%4"), "%4");
+    }
+
+    #[test]
+    fn test_percent_decode_encoded_plus() {
+        assert_eq!(percent_decode("a%2Bb+c"), "a+b c");
+    }
+}
@@ -0,0 +1,133 @@
+use crate::ast::RowRange;
+use crate::error::{Error, ParseError};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{digit1, space0},
+    combinator::{all_consuming, map_res, opt},
+    sequence::{delimited, separated_pair},
+    IResult,
+};
+
+/// The only range unit PostgREST supports
+const ITEMS_UNIT: &str = "items";
+
+fn bound(input: &str) -> IResult<&str, u64> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// Parses "lower-upper" or "lower-"
+fn parse_bounds(input: &str) -> IResult<&str, (u64, Option<u64>)> {
+    all_consuming(delimited(
+        space0,
+        separated_pair(bound, tag("-"), opt(bound)),
+        space0,
+    ))(input)
+}
+
+/// Parses a `Range` header value, with the optional `Range-Unit` header
+///
+/// Accepts `0-24`, an open range such as `25-`, and the unit-prefixed form
+/// `items=0-24`. Both bounds are inclusive.
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::parser::parse_range_header;
+///
+/// let range = parse_range_header("0-24", Some("items")).unwrap();
+/// assert_eq!((range.offset, range.limit), (0, Some(25)));
+///
+/// let range = parse_range_header("items=25-", None).unwrap();
+/// assert_eq!((range.offset, range.limit), (25, None));
+///
+/// assert!(parse_range_header("24-0", None).is_err());
+/// ```
+pub fn parse_range_header(range: &str, unit: Option<&str>) -> Result<RowRange, Error> {
+    let invalid = || Error::Parse(ParseError::InvalidRange(range.to_string()));
+
+    let (unit, bounds) = match range.split_once('=') {
+        Some((prefix, bounds)) => (Some(prefix), bounds),
+        None => (unit, range),
+    };
+    if unit.is_some_and(|unit| !unit.trim().eq_ignore_ascii_case(ITEMS_UNIT)) {
+        return Err(invalid());
+    }
+
+    let (_, (lower, upper)) = parse_bounds(bounds).map_err(|_| invalid())?;
+    let limit = match upper {
+        Some(upper) => Some(
+            upper
+                .checked_sub(lower)
+                .and_then(|span| span.checked_add(1))
+                .ok_or_else(invalid)?,
+        ),
+        None => None,
+    };
+
+    Ok(RowRange::new(lower, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_closed_range() {
+        assert_eq!(
+            parse_range_header("0-24", None).unwrap(),
+            RowRange::new(0, Some(25))
+        );
+        assert_eq!(
+            parse_range_header("5-5", Some("items")).unwrap(),
+            RowRange::new(5, Some(1))
+        );
+    }
+
+    #[test]
+    fn test_parse_open_range() {
+        assert_eq!(
+            parse_range_header("10-", None).unwrap(),
+            RowRange::new(10, None)
+        );
+    }
+
+    #[test]
+    fn test_parse_unit_prefixed_range() {
+        assert_eq!(
+            parse_range_header("items=0-9", None).unwrap(),
+            RowRange::new(0, Some(10))
+        );
+        assert_eq!(
+            parse_range_header(" 0-9 ", Some("Items")).unwrap(),
+            RowRange::new(0, Some(10))
+        );
+    }
+
+    #[test]
+    fn test_parse_range_up_to_u64_max() {
+        assert_eq!(
+            parse_range_header("1-18446744073709551615", None).unwrap(),
+            RowRange::new(1, Some(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_ranges() {
+        for (range, unit) in [
+            ("", None),
+            ("-5", None),
+            ("a-b", None),
+            ("0-9,20-29", None),
+            ("10-2", None),
+            ("0-9", Some("bytes")),
+            ("bytes=0-9", None),
+            ("0-18446744073709551615", None),
+        ] {
+            assert_eq!(
+                parse_range_header(range, unit),
+                Err(Error::Parse(ParseError::InvalidRange(range.to_string()))),
+                "{range}"
+            );
+        }
+    }
+}
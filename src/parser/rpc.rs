@@ -1,6 +1,6 @@
-use crate::ast::RpcParams;
+use crate::ast::{Function, RpcBody, RpcParams};
 use crate::error::{Error, ParseError};
-use crate::parser::{parse_json_body, parse_order, parse_select};
+use crate::parser::{parse_json_body, parse_order, parse_query_pairs, parse_select};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -26,25 +26,108 @@ pub fn parse_rpc_params(
     query_string: &str,
     body: Option<&str>,
 ) -> Result<RpcParams, Error> {
-    // Parse arguments from body if present
-    let args = if let Some(body_str) = body {
-        let json_value = parse_json_body(body_str)?;
-        validate_rpc_args(json_value)?
-    } else {
-        HashMap::new()
-    };
+    parse_rpc_params_with_functions("POST", function_name, query_string, body, &[])
+}
 
-    let mut params = RpcParams::new(function_name, args);
+/// Parses RPC parameters using the function's metadata from the schema cache
+///
+/// On `GET` and `HEAD`, query keys matching a parameter name of one of the
+/// candidate `functions` (its overloads) are passed as arguments, as with
+/// `GET /rpc/search?term=foo`; the other non-reserved keys filter the results.
+/// Other methods take their arguments from the body only, and without
+/// candidates every such key is a filter, like [`parse_rpc_params`]. A key
+/// naming a `VARIADIC` parameter may repeat, as in `?ids=1&ids=2`, and its
+/// values form an array.
+///
+/// A function with a single unnamed `json` or `jsonb` parameter receives the
+/// whole body, whatever its shape, as [`RpcBody::SingleObject`]. Otherwise an
+/// array of objects is a batch of calls, [`RpcBody::Batch`].
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::{parse_rpc_params_with_functions, Function, FunctionParam};
+/// use serde_json::json;
+///
+/// let search = Function::new("public", "search").with_params(vec![FunctionParam::new("term", "text")]);
+/// let params =
+///     parse_rpc_params_with_functions("GET", "search", "term=foo&price=lt.10", None, &[search])
+///         .unwrap();
+/// assert_eq!(params.args.get("term"), Some(&json!("foo")));
+/// assert_eq!(params.filters.len(), 1);
+/// ```
+pub fn parse_rpc_params_with_functions(
+    method: &str,
+    function_name: &str,
+    query_string: &str,
+    body: Option<&str>,
+    functions: &[Function],
+) -> Result<RpcParams, Error> {
+    // Parse arguments from body if present; a function with a single unnamed
+    // json/jsonb parameter gets the whole body
+    let mut rpc_body = RpcBody::Named;
+    let mut args = HashMap::new();
+    if let Some(body_str) = body {
+        let json_value = parse_json_body(body_str)?;
+        match functions {
+            [function] if takes_json_body(function) => {
+                rpc_body = RpcBody::SingleObject(json_value);
+            }
+            _ => match json_value {
+                Value::Array(rows) => {
+                    let rows = rows
+                        .into_iter()
+                        .map(validate_rpc_args)
+                        .collect::<Result<_, _>>()?;
+                    rpc_body = RpcBody::Batch(rows);
+                }
+                value => args = validate_rpc_args(value)?,
+            },
+        }
+    }
 
     // Parse query parameters
     let query_params = parse_query_params(query_string);
 
-    // Parse filters - all non-reserved keys become filters
-    let filter_pairs: Vec<(String, String)> = query_params
-        .iter()
-        .filter(|(k, _)| !is_reserved_key(k))
-        .map(|(k, v)| (k.clone(), v.clone()))
-        .collect();
+    // On reads, keys naming a function parameter are arguments, the other
+    // non-reserved keys filters; the values of a repeated VARIADIC key are
+    // collected into an array. The overload is only picked when building the
+    // query, so every candidate with the parameter must agree on whether it is
+    // VARIADIC
+    let is_read = matches!(method.to_uppercase().as_str(), "GET" | "HEAD");
+    let query_functions = if is_read { functions } else { &[] };
+    let variadic = |key: &str| -> Result<Option<bool>, Error> {
+        let mut variadic = query_functions
+            .iter()
+            .filter_map(|function| function.param(key))
+            .map(|param| param.variadic);
+        match variadic.next() {
+            Some(first) if variadic.any(|other| other != first) => Err(Error::Parse(
+                ParseError::AmbiguousVariadicParameter(function_name.to_string(), key.to_string()),
+            )),
+            first => Ok(first),
+        }
+    };
+    let mut filter_pairs: Vec<(String, String)> = Vec::new();
+    for (key, value) in parse_query_pairs(query_string) {
+        if is_reserved_key(&key) {
+            continue;
+        }
+        match variadic(&key)? {
+            Some(true) => {
+                let values = args.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(values) = values {
+                    values.push(Value::String(value));
+                }
+            }
+            Some(false) => {
+                args.insert(key, Value::String(value));
+            }
+            None => filter_pairs.push((key, value)),
+        }
+    }
+
+    let mut params = RpcParams::new(function_name, args).with_body(rpc_body);
 
     if !filter_pairs.is_empty() {
         let parsed_params = crate::parse_params_from_pairs(filter_pairs)?;
@@ -82,6 +165,12 @@ pub fn parse_rpc_params(
     Ok(params)
 }
 
+fn takes_json_body(function: &Function) -> bool {
+    function
+        .single_unnamed_param()
+        .is_some_and(|param| matches!(param.type_.as_str(), "json" | "jsonb"))
+}
+
 fn validate_rpc_args(value: Value) -> Result<HashMap<String, Value>, Error> {
     match value {
         Value::Object(map) => {
@@ -92,27 +181,13 @@ fn validate_rpc_args(value: Value) -> Result<HashMap<String, Value>, Error> {
             Ok(hash_map)
         }
         _ => Err(Error::Parse(ParseError::InvalidJsonBody(
-            "RPC arguments must be a JSON object".to_string(),
+            "RPC arguments must be a JSON object or an array of objects".to_string(),
         ))),
     }
 }
 
 fn parse_query_params(query_string: &str) -> HashMap<String, String> {
-    if query_string.is_empty() {
-        return HashMap::new();
-    }
-
-    query_string
-        .split('&')
-        .filter_map(|pair| {
-            let parts: Vec<&str> = pair.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                Some((parts[0].to_string(), parts[1].to_string()))
-            } else {
-                None
-            }
-        })
-        .collect()
+    parse_query_pairs(query_string).into_iter().collect()
 }
 
 fn is_reserved_key(key: &str) -> bool {
@@ -163,6 +238,14 @@ mod tests {
         assert_eq!(params.filters.len(), 2);
     }
 
+    #[test]
+    fn test_parse_rpc_params_percent_decoded() {
+        let params =
+            parse_rpc_params("list_users", "order=created_at.desc%2Cname.asc", None).unwrap();
+
+        assert_eq!(params.order.len(), 2);
+    }
+
     #[test]
     fn test_parse_rpc_params_with_select() {
         let params = parse_rpc_params("get_posts", "select=id,title,author", None).unwrap();
@@ -192,4 +275,114 @@ mod tests {
         assert_eq!(params.limit, Some(50));
         assert!(params.returning.is_some());
     }
+
+    #[test]
+    fn test_query_keys_matching_params_become_args() {
+        use crate::ast::FunctionParam;
+
+        let search = Function::new("public", "search").with_params(vec![
+            FunctionParam::new("term", "text"),
+            FunctionParam::new("max_price", "numeric").with_default(true),
+        ]);
+        let params = parse_rpc_params_with_functions(
+            "GET",
+            "search",
+            "term=foo&category=eq.books&limit=5",
+            None,
+            &[search],
+        )
+        .unwrap();
+
+        assert_eq!(params.args.len(), 1);
+        assert_eq!(
+            params.args.get("term"),
+            Some(&Value::String("foo".to_string()))
+        );
+        assert_eq!(params.filters.len(), 1);
+        assert_eq!(params.limit, Some(5));
+    }
+
+    #[test]
+    fn test_query_keys_without_functions_are_filters() {
+        // Without metadata, "term=foo" is read as a filter and has no operator
+        assert!(parse_rpc_params("search", "term=foo", None).is_err());
+        assert!(parse_rpc_params_with_functions("GET", "search", "term=foo", None, &[]).is_err());
+    }
+
+    #[test]
+    fn test_single_unnamed_json_param_gets_whole_body() {
+        use crate::ast::FunctionParam;
+
+        let ingest =
+            Function::new("public", "ingest").with_params(vec![FunctionParam::new("", "jsonb")]);
+        let body = r#"[{"kind": "click"}, {"kind": "view"}]"#;
+        let params =
+            parse_rpc_params_with_functions("POST", "ingest", "", Some(body), &[ingest]).unwrap();
+
+        assert!(params.args.is_empty());
+        assert_eq!(
+            params.body,
+            RpcBody::SingleObject(serde_json::json!([{"kind": "click"}, {"kind": "view"}]))
+        );
+    }
+
+    #[test]
+    fn test_array_body_is_a_batch() {
+        let body = r#"[{"a": 1, "b": 2}, {"a": 3}]"#;
+        let params = parse_rpc_params("add", "", Some(body)).unwrap();
+
+        assert!(params.args.is_empty());
+        match params.body {
+            RpcBody::Batch(rows) => {
+                assert_eq!(rows.len(), 2);
+                assert_eq!(rows[1].get("a"), Some(&Value::Number(3.into())));
+            }
+            body => panic!("Expected a batch, got {body:?}"),
+        }
+
+        assert!(parse_rpc_params("add", "", Some(r#"[{"a": 1}, 2]"#)).is_err());
+    }
+
+    #[test]
+    fn test_repeated_variadic_query_keys_form_an_array() {
+        use crate::ast::FunctionParam;
+
+        let by_ids = Function::new("public", "by_ids").with_params(vec![
+            FunctionParam::new("label", "text"),
+            FunctionParam::new("ids", "integer[]").as_variadic(),
+        ]);
+        let params = parse_rpc_params_with_functions(
+            "GET",
+            "by_ids",
+            "ids=1&label=a&ids=3&label=b",
+            None,
+            &[by_ids],
+        )
+        .unwrap();
+
+        assert_eq!(params.args.get("ids"), Some(&serde_json::json!(["1", "3"])));
+        // A repeated non-variadic key keeps its last value
+        assert_eq!(params.args.get("label"), Some(&serde_json::json!("b")));
+    }
+
+    #[test]
+    fn test_variadic_query_key_must_agree_across_overloads() {
+        use crate::ast::FunctionParam;
+
+        let by_ids = Function::new("public", "by_ids").with_params(vec![FunctionParam::new(
+            "ids",
+            "integer[]",
+        )
+        .as_variadic()]);
+        let by_id = Function::new("public", "by_ids")
+            .with_params(vec![FunctionParam::new("ids", "integer")]);
+        let result =
+            parse_rpc_params_with_functions("GET", "by_ids", "ids=1", None, &[by_id, by_ids]);
+
+        assert!(matches!(
+            result,
+            Err(Error::Parse(ParseError::AmbiguousVariadicParameter(ref function, ref param)))
+                if function == "by_ids" && param == "ids"
+        ));
+    }
 }
@@ -1,4 +1,5 @@
-use crate::ast::{ItemType, JsonOp, SelectItem};
+use super::common::parse_field_fallback;
+use crate::ast::{Aggregate, AggregateFunction, ItemType, JsonOp, SelectItem};
 use crate::error::ParseError;
 
 /// Parses a PostgREST select clause into a list of select items.
@@ -15,6 +16,7 @@ use crate::error::ParseError;
 /// - Type cast: `price::numeric`
 /// - Nested relations: `users(id,name,posts(title))`
 /// - Spread operator: `...foreign_table(col1,col2)`
+/// - Aggregates: `count()`, `price.sum()`, `total:price.avg()::int`
 ///
 /// # Examples
 ///
@@ -46,6 +48,11 @@ use crate::error::ParseError;
 /// // Type cast
 /// let items = parse_select("price::numeric,created_at::text").unwrap();
 /// assert_eq!(items.len(), 2);
+///
+/// // Aggregates
+/// let items = parse_select("category,price.sum(),count()").unwrap();
+/// assert!(items[1].is_aggregate());
+/// assert_eq!(items[2].name, "*");
 /// ```
 ///
 /// # Errors
@@ -133,6 +140,12 @@ fn parse_items(tokens: &[SelectToken]) -> Result<Vec<SelectItem>, ParseError> {
     while index < tokens.len() {
         match &tokens[index] {
             SelectToken::Text(text) => {
+                if let Some((aggregate, next_index)) = parse_aggregate(tokens, index)? {
+                    items.push(aggregate);
+                    index = next_index;
+                    continue;
+                }
+
                 let has_children =
                     index + 1 < tokens.len() && matches!(tokens[index + 1], SelectToken::OpenParen);
 
@@ -178,6 +191,12 @@ fn parse_nested_children(
     while index < tokens.len() && depth > 0 {
         match &tokens[index] {
             SelectToken::Text(text) => {
+                if let Some((aggregate, next_index)) = parse_aggregate(tokens, index)? {
+                    children.push(aggregate);
+                    index = next_index;
+                    continue;
+                }
+
                 let has_children =
                     index + 1 < tokens.len() && matches!(tokens[index + 1], SelectToken::OpenParen);
 
@@ -218,6 +237,82 @@ fn parse_nested_children(
     Ok((children, index))
 }
 
+/// Recognises `column.fn()` and `count()` at `index`, with an optional
+/// `::type` cast on the result, returning the item and the next token index
+fn parse_aggregate(
+    tokens: &[SelectToken],
+    index: usize,
+) -> Result<Option<(SelectItem, usize)>, ParseError> {
+    let text = match (
+        tokens.get(index),
+        tokens.get(index + 1),
+        tokens.get(index + 2),
+    ) {
+        (
+            Some(SelectToken::Text(text)),
+            Some(SelectToken::OpenParen),
+            Some(SelectToken::CloseParen),
+        ) => text.trim(),
+        _ => return Ok(None),
+    };
+
+    let (name_part, alias) = extract_alias(text)?;
+    let (column, function) = match name_part.rsplit_once('.') {
+        Some((column, function)) => (column.trim(), function),
+        None => ("", name_part.as_str()),
+    };
+
+    let function = match AggregateFunction::from_name(function) {
+        Some(function) => function,
+        None => return Ok(None),
+    };
+
+    let mut item = if column.is_empty() {
+        if function != AggregateFunction::Count {
+            return Err(ParseError::InvalidSelectItem(format!(
+                "{}() requires a column",
+                function.name()
+            )));
+        }
+        SelectItem::field("*")
+    } else {
+        field_item(column)?
+    };
+
+    let mut aggregate = Aggregate::new(function);
+    let mut next_index = index + 3;
+    if let Some(SelectToken::Text(rest)) = tokens.get(next_index) {
+        let cast = rest.trim().strip_prefix("::").ok_or_else(|| {
+            ParseError::InvalidSelectItem(format!(
+                "unexpected '{}' after {}()",
+                rest,
+                function.name()
+            ))
+        })?;
+        if cast.is_empty() {
+            return Err(ParseError::InvalidTypeCast(rest.trim().to_string()));
+        }
+        aggregate = aggregate.with_cast(cast);
+        next_index += 1;
+    }
+
+    item = item.with_aggregate(aggregate);
+    if let Some(alias_name) = alias {
+        item = item.with_alias(alias_name);
+    }
+
+    Ok(Some((item, next_index)))
+}
+
+fn field_item(name: &str) -> Result<SelectItem, ParseError> {
+    if name.contains("->") || name.contains("::") {
+        let field = parse_field_fallback(name)?;
+        Ok(SelectItem::field(field.name.clone()).with_field(field))
+    } else {
+        Ok(SelectItem::field(name))
+    }
+}
+
 fn parse_item_text(text: &str, has_children: bool) -> Result<SelectItem, ParseError> {
     let trimmed = text.trim();
 
@@ -243,7 +338,7 @@ fn parse_item_text(text: &str, has_children: bool) -> Result<SelectItem, ParseEr
     };
 
     let mut item = match item_type {
-        ItemType::Field => SelectItem::field(name.clone()),
+        ItemType::Field => field_item(&name)?,
         ItemType::Relation => SelectItem::relation(name.clone()),
         ItemType::Spread => SelectItem::spread(name.clone()),
     };
@@ -260,18 +355,18 @@ fn parse_item_text(text: &str, has_children: bool) -> Result<SelectItem, ParseEr
 }
 
 fn extract_alias(text: &str) -> Result<(String, Option<String>), ParseError> {
-    if text.contains(':') {
-        let parts: Vec<&str> = text.splitn(2, ':').collect();
-        if parts.len() == 2 {
-            Ok((
-                parts[1].trim().to_string(),
-                Some(parts[0].trim().to_string()),
-            ))
-        } else {
-            Ok((text.to_string(), None))
-        }
-    } else {
-        Ok((text.to_string(), None))
+    // The alias separator is a single ':'; '::' introduces a cast
+    let bytes = text.as_bytes();
+    let separator = (0..bytes.len()).find(|&i| {
+        bytes[i] == b':' && bytes.get(i + 1) != Some(&b':') && (i == 0 || bytes[i - 1] != b':')
+    });
+
+    match separator {
+        Some(pos) => Ok((
+            text[pos + 1..].trim().to_string(),
+            Some(text[..pos].trim().to_string()),
+        )),
+        None => Ok((text.to_string(), None)),
     }
 }
 
@@ -345,6 +440,105 @@ mod tests {
         assert_eq!(items[0].alias, Some("user_name".to_string()));
     }
 
+    #[test]
+    fn test_parse_select_json_path_field() {
+        let items = parse_select("data->address->>city").unwrap();
+        assert_eq!(items[0].name, "data");
+        assert!(items[0].alias.is_none());
+        let field = items[0].field.as_ref().unwrap();
+        assert_eq!(
+            field.json_path,
+            vec![
+                JsonOp::Arrow("address".to_string()),
+                JsonOp::DoubleArrow("city".to_string()),
+            ]
+        );
+        assert_eq!(items[0].output_name(), "city");
+    }
+
+    #[test]
+    fn test_parse_select_cast_is_not_an_alias() {
+        let items = parse_select("price::text,total:amount::numeric,data->>qty::int").unwrap();
+
+        assert_eq!(items[0].name, "price");
+        assert!(items[0].alias.is_none());
+        assert_eq!(
+            items[0].field.as_ref().unwrap().cast,
+            Some("text".to_string())
+        );
+
+        assert_eq!(items[1].name, "amount");
+        assert_eq!(items[1].alias, Some("total".to_string()));
+        assert_eq!(
+            items[1].field.as_ref().unwrap().cast,
+            Some("numeric".to_string())
+        );
+
+        let field = items[2].field.as_ref().unwrap();
+        assert_eq!(
+            field.json_path,
+            vec![JsonOp::DoubleArrow("qty".to_string())]
+        );
+        assert_eq!(field.cast, Some("int".to_string()));
+    }
+
+    #[test]
+    fn test_parse_select_plain_field_has_no_field_details() {
+        let items = parse_select("id,name").unwrap();
+        assert!(items.iter().all(|item| item.field.is_none()));
+    }
+
+    #[test]
+    fn test_parse_select_aggregates() {
+        let items =
+            parse_select("category,total:price.sum()::int,count(),data->>score.avg()").unwrap();
+        assert_eq!(items.len(), 4);
+
+        assert!(!items[0].is_aggregate());
+
+        assert_eq!(items[1].name, "price");
+        assert_eq!(items[1].alias, Some("total".to_string()));
+        let aggregate = items[1].aggregate.as_ref().unwrap();
+        assert_eq!(aggregate.function, AggregateFunction::Sum);
+        assert_eq!(aggregate.cast, Some("int".to_string()));
+
+        assert_eq!(items[2].name, "*");
+        assert_eq!(items[2].output_name(), "count");
+        assert_eq!(items[2].aggregate.as_ref().unwrap().cast, None);
+
+        assert_eq!(items[3].name, "data");
+        assert!(items[3].field.is_some());
+        assert_eq!(
+            items[3].aggregate.as_ref().unwrap().function,
+            AggregateFunction::Avg
+        );
+    }
+
+    #[test]
+    fn test_parse_select_aggregates_in_relation() {
+        let items = parse_select("id,orders(status,count(),total_amount.max())").unwrap();
+        let children = items[1].children.as_ref().unwrap();
+        assert_eq!(children.len(), 3);
+        assert!(!children[0].is_aggregate());
+        assert_eq!(children[1].output_name(), "count");
+        assert_eq!(children[2].output_name(), "max");
+    }
+
+    #[test]
+    fn test_parse_select_aggregate_errors() {
+        assert!(matches!(
+            parse_select("sum()"),
+            Err(ParseError::InvalidSelectItem(_))
+        ));
+        assert!(matches!(
+            parse_select("count()::"),
+            Err(ParseError::InvalidTypeCast(_))
+        ));
+        // Empty parentheses on a non-aggregate name are still an (empty) embed
+        let items = parse_select("orders()").unwrap();
+        assert_eq!(items[0].item_type, ItemType::Relation);
+    }
+
     #[test]
     fn test_parse_select_with_relation() {
         let result = parse_select("id,client(id,name)");
@@ -1,13 +1,135 @@
+//! Schema introspection and caching for relation resolution.
+//!
+//! This module provides database schema introspection to resolve foreign key
+//! relationships, enabling proper JOIN generation for resource embedding.
+
+use crate::ast::{Function, FunctionReturn, Volatility};
+use crate::error::SqlError;
+use crate::sql::fragment::Ident;
 use serde::{Deserialize, Serialize};
-///! Schema introspection and caching for relation resolution.
-///!
-///! This module provides database schema introspection to resolve foreign key
-///! relationships, enabling proper JOIN generation for resource embedding.
 use std::collections::HashMap;
 
 #[cfg(feature = "postgres")]
 use sqlx::{PgPool, Row};
 
+/// Introspection query listing every foreign key with its ordered column lists.
+///
+/// Based on PostgREST's approach using system catalogs. Composite keys keep their
+/// column order by unnesting `conkey`/`confkey` with ordinality.
+pub(crate) const FOREIGN_KEYS_QUERY: &str = r#"
+    SELECT
+        con.conname AS constraint_name,
+        sn.nspname AS from_schema,
+        sc.relname AS from_table,
+        ARRAY(
+            SELECT sa.attname::text
+            FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+            JOIN pg_attribute sa ON sa.attrelid = con.conrelid AND sa.attnum = k.attnum
+            ORDER BY k.ord
+        ) AS from_columns,
+        tn.nspname AS to_schema,
+        tc.relname AS to_table,
+        ARRAY(
+            SELECT ta.attname::text
+            FROM unnest(con.confkey) WITH ORDINALITY AS k(attnum, ord)
+            JOIN pg_attribute ta ON ta.attrelid = con.confrelid AND ta.attnum = k.attnum
+            ORDER BY k.ord
+        ) AS to_columns
+    FROM pg_constraint con
+    JOIN pg_class sc ON sc.oid = con.conrelid
+    JOIN pg_namespace sn ON sn.oid = sc.relnamespace
+    JOIN pg_class tc ON tc.oid = con.confrelid
+    JOIN pg_namespace tn ON tn.oid = tc.relnamespace
+    WHERE con.contype = 'f'
+      AND sn.nspname NOT IN ('pg_catalog', 'information_schema')
+    ORDER BY sn.nspname, sc.relname, con.conname
+"#;
+
+/// Introspection query listing the primary key columns of every table, in key order
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub(crate) const PRIMARY_KEYS_QUERY: &str = r#"
+    SELECT
+        n.nspname AS table_schema,
+        c.relname AS table_name,
+        ARRAY(
+            SELECT a.attname::text
+            FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+            JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+            ORDER BY k.ord
+        ) AS columns
+    FROM pg_constraint con
+    JOIN pg_class c ON c.oid = con.conrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE con.contype = 'p'
+      AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+"#;
+
+/// Introspection query listing the columns of every unique constraint, in key order
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub(crate) const UNIQUE_KEYS_QUERY: &str = r#"
+    SELECT
+        n.nspname AS table_schema,
+        c.relname AS table_name,
+        ARRAY(
+            SELECT a.attname::text
+            FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+            JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+            ORDER BY k.ord
+        ) AS columns
+    FROM pg_constraint con
+    JOIN pg_class c ON c.oid = con.conrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE con.contype = 'u'
+      AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+"#;
+
+/// Introspection query listing every function with its input parameters,
+/// volatility and return type.
+///
+/// Parameter names and types are the input (`IN`, `INOUT` and `VARIADIC`)
+/// arguments in declaration order; unnamed parameters have an empty name. The
+/// last `default_count` of them have defaults, and the last one is `VARIADIC`
+/// when `variadic` is set. Trigger functions and procedures are left out since
+/// they cannot be called through `/rpc`.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub(crate) const FUNCTIONS_QUERY: &str = r#"
+    SELECT
+        n.nspname AS function_schema,
+        p.proname AS function_name,
+        ARRAY(
+            SELECT COALESCE(args.name, '')
+            FROM unnest(
+                COALESCE(p.proallargtypes, p.proargtypes::oid[]),
+                COALESCE(p.proargmodes, array_fill('i'::"char", ARRAY[p.pronargs::int])),
+                COALESCE(p.proargnames, array_fill(''::text, ARRAY[p.pronargs::int]))
+            ) WITH ORDINALITY AS args(type_oid, mode, name, ord)
+            WHERE args.mode IN ('i', 'b', 'v')
+            ORDER BY args.ord
+        ) AS arg_names,
+        ARRAY(
+            SELECT format_type(args.type_oid, NULL)
+            FROM unnest(
+                COALESCE(p.proallargtypes, p.proargtypes::oid[]),
+                COALESCE(p.proargmodes, array_fill('i'::"char", ARRAY[p.pronargs::int]))
+            ) WITH ORDINALITY AS args(type_oid, mode, ord)
+            WHERE args.mode IN ('i', 'b', 'v')
+            ORDER BY args.ord
+        ) AS arg_types,
+        p.pronargdefaults::int AS default_count,
+        p.provariadic <> 0 AS variadic,
+        p.provolatile::text AS volatility,
+        p.proretset AS returns_set,
+        format_type(p.prorettype, NULL) AS return_type,
+        t.typtype::text AS return_type_kind
+    FROM pg_proc p
+    JOIN pg_namespace n ON n.oid = p.pronamespace
+    JOIN pg_type t ON t.oid = p.prorettype
+    WHERE p.prokind = 'f'
+      AND p.prorettype <> 'trigger'::regtype
+      AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+    ORDER BY n.nspname, p.proname, p.oid
+"#;
+
 /// A foreign key relationship between two tables.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ForeignKey {
@@ -15,14 +137,14 @@ pub struct ForeignKey {
     pub from_schema: String,
     /// Source table name
     pub from_table: String,
-    /// Source column name
-    pub from_column: String,
+    /// Source column names, in constraint order
+    pub from_columns: Vec<String>,
     /// Target table schema
     pub to_schema: String,
     /// Target table name
     pub to_table: String,
-    /// Target column name
-    pub to_column: String,
+    /// Target column names, paired positionally with `from_columns`
+    pub to_columns: Vec<String>,
     /// Foreign key constraint name
     pub constraint_name: String,
 }
@@ -33,17 +155,27 @@ impl ForeignKey {
         self.from_table == from_table && self.to_table == to_table
     }
 
-    /// Returns the JOIN ON clause for this foreign key
+    /// Returns the JOIN ON clause for this foreign key, one equality per column pair
     pub fn join_condition(&self, from_alias: &str, to_alias: &str) -> String {
-        format!(
-            "\"{}\".\"{}\" = \"{}\".\"{}\"",
-            from_alias, &self.from_column, to_alias, &self.to_column
-        )
+        self.from_columns
+            .iter()
+            .zip(&self.to_columns)
+            .map(|(from_column, to_column)| {
+                format!(
+                    "{}.{} = {}.{}",
+                    Ident(from_alias),
+                    Ident(from_column),
+                    Ident(to_alias),
+                    Ident(to_column)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ")
     }
 }
 
 /// Type of relationship between tables
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RelationType {
     /// Many-to-One: many rows in source table reference one row in target
     ManyToOne,
@@ -52,7 +184,9 @@ pub enum RelationType {
     /// Many-to-Many: through a junction table
     ManyToMany {
         /// The junction table name
-        junction_table: &'static str,
+        junction_table: String,
+        /// Foreign key from the junction table to the source table
+        source_key: ForeignKey,
     },
 }
 
@@ -63,12 +197,42 @@ pub struct Relationship {
     pub from_table: String,
     /// Target table (the relation name used in queries)
     pub to_table: String,
-    /// Foreign key used for the relationship
+    /// Foreign key used for the relationship (for many-to-many, the junction
+    /// table's foreign key to the target table)
     pub foreign_key: ForeignKey,
     /// Type of relationship
     pub relation_type: RelationType,
 }
 
+impl Relationship {
+    /// Returns true if `hint` selects this relationship.
+    ///
+    /// A hint may name the foreign key constraint or its column (for single-column
+    /// keys); many-to-many relationships also match their junction table or either
+    /// of the junction's foreign keys.
+    pub fn matches_hint(&self, hint: &str) -> bool {
+        let key_matches = |fk: &ForeignKey| fk.constraint_name == hint || fk.from_columns == [hint];
+
+        match &self.relation_type {
+            RelationType::ManyToOne | RelationType::OneToMany => key_matches(&self.foreign_key),
+            RelationType::ManyToMany {
+                junction_table,
+                source_key,
+            } => {
+                junction_table == hint || key_matches(&self.foreign_key) || key_matches(source_key)
+            }
+        }
+    }
+
+    /// Returns the `!hint` that selects this relationship unambiguously
+    pub fn hint(&self) -> &str {
+        match &self.relation_type {
+            RelationType::ManyToOne | RelationType::OneToMany => &self.foreign_key.constraint_name,
+            RelationType::ManyToMany { junction_table, .. } => junction_table,
+        }
+    }
+}
+
 /// Cache of database schema information
 #[derive(Debug, Clone, Default)]
 pub struct SchemaCache {
@@ -76,6 +240,12 @@ pub struct SchemaCache {
     foreign_keys: HashMap<(String, String), Vec<ForeignKey>>,
     /// Reverse lookup: which tables reference this table
     reverse_fks: HashMap<(String, String), Vec<ForeignKey>>,
+    /// Primary key columns, indexed by (schema, table)
+    primary_keys: HashMap<(String, String), Vec<String>>,
+    /// Unique constraint columns, indexed by (schema, table)
+    unique_keys: HashMap<(String, String), Vec<Vec<String>>>,
+    /// Functions, indexed by (schema, name); overloads share an entry
+    functions: HashMap<(String, String), Vec<Function>>,
 }
 
 impl SchemaCache {
@@ -90,101 +260,295 @@ impl SchemaCache {
         let mut cache = Self::new();
 
         // Query all foreign keys from pg_catalog (more reliable than information_schema)
-        // Based on PostgREST's approach using system catalogs
-        let fks = sqlx::query(
-            r#"
-            SELECT
-                con.conname AS constraint_name,
-                sn.nspname AS from_schema,
-                sc.relname AS from_table,
-                sa.attname AS from_column,
-                tn.nspname AS to_schema,
-                tc.relname AS to_table,
-                ta.attname AS to_column
-            FROM pg_constraint con
-            JOIN pg_class sc ON sc.oid = con.conrelid
-            JOIN pg_namespace sn ON sn.oid = sc.relnamespace
-            JOIN pg_class tc ON tc.oid = con.confrelid
-            JOIN pg_namespace tn ON tn.oid = tc.relnamespace
-            JOIN pg_attribute sa ON sa.attrelid = sc.oid AND sa.attnum = con.conkey[1]
-            JOIN pg_attribute ta ON ta.attrelid = tc.oid AND ta.attnum = con.confkey[1]
-            WHERE con.contype = 'f'
-              AND sn.nspname NOT IN ('pg_catalog', 'information_schema')
-              AND array_length(con.conkey, 1) = 1
-            ORDER BY sn.nspname, sc.relname, con.conname
-            "#,
-        )
-        .fetch_all(pool)
-        .await?;
+        let fks = sqlx::query(FOREIGN_KEYS_QUERY).fetch_all(pool).await?;
 
         for row in fks {
             let fk = ForeignKey {
                 from_schema: row.get("from_schema"),
                 from_table: row.get("from_table"),
-                from_column: row.get("from_column"),
+                from_columns: row.get("from_columns"),
                 to_schema: row.get("to_schema"),
                 to_table: row.get("to_table"),
-                to_column: row.get("to_column"),
+                to_columns: row.get("to_columns"),
                 constraint_name: row.get("constraint_name"),
             };
 
-            // Index by source table
-            cache
-                .foreign_keys
-                .entry((fk.from_schema.clone(), fk.from_table.clone()))
-                .or_default()
-                .push(fk.clone());
-
-            // Index by target table (reverse lookup)
-            cache
-                .reverse_fks
-                .entry((fk.to_schema.clone(), fk.to_table.clone()))
-                .or_default()
-                .push(fk);
+            cache.add_foreign_key(fk);
+        }
+
+        let pks = sqlx::query(PRIMARY_KEYS_QUERY).fetch_all(pool).await?;
+        for row in pks {
+            cache.add_primary_key(
+                row.get::<String, _>("table_schema"),
+                row.get::<String, _>("table_name"),
+                row.get("columns"),
+            );
+        }
+
+        let uniques = sqlx::query(UNIQUE_KEYS_QUERY).fetch_all(pool).await?;
+        for row in uniques {
+            cache.add_unique_key(
+                row.get::<String, _>("table_schema"),
+                row.get::<String, _>("table_name"),
+                row.get("columns"),
+            );
+        }
+
+        let functions = sqlx::query(FUNCTIONS_QUERY).fetch_all(pool).await?;
+        for row in functions {
+            let names: Vec<String> = row.get("arg_names");
+            let types: Vec<String> = row.get("arg_types");
+            let default_count: i32 = row.get("default_count");
+            let first_default = names.len().saturating_sub(default_count as usize);
+            let mut params: Vec<_> = names
+                .into_iter()
+                .zip(types)
+                .enumerate()
+                .map(|(i, (name, type_))| {
+                    crate::ast::FunctionParam::new(name, type_).with_default(i >= first_default)
+                })
+                .collect();
+            if let Some(last) = params.last_mut().filter(|_| row.get("variadic")) {
+                last.variadic = true;
+            }
+
+            cache.add_function(
+                Function::new(
+                    row.get::<String, _>("function_schema"),
+                    row.get::<String, _>("function_name"),
+                )
+                .with_params(params)
+                .with_volatility(volatility_from_catalog(row.get("volatility")))
+                .returning(function_return_from_catalog(
+                    row.get("return_type"),
+                    row.get("return_type_kind"),
+                    row.get("returns_set"),
+                )),
+            );
         }
 
         Ok(cache)
     }
 
-    /// Finds a foreign key relationship from one table to another
+    /// Registers a function; functions sharing a schema and name are overloads
+    pub fn add_function(&mut self, function: Function) {
+        self.functions
+            .entry((function.schema.clone(), function.name.clone()))
+            .or_default()
+            .push(function);
+    }
+
+    /// Gets every overload of a function, empty when the function is unknown
+    pub fn get_functions(&self, schema: &str, name: &str) -> &[Function] {
+        self.functions
+            .get(&(schema.to_string(), name.to_string()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Registers the primary key columns of a table
+    pub fn add_primary_key(
+        &mut self,
+        schema: impl Into<String>,
+        table: impl Into<String>,
+        columns: Vec<String>,
+    ) {
+        self.primary_keys
+            .insert((schema.into(), table.into()), columns);
+    }
+
+    /// Gets the primary key columns of a table, if it has one
+    pub fn get_primary_key(&self, schema: &str, table: &str) -> Option<&[String]> {
+        self.primary_keys
+            .get(&(schema.to_string(), table.to_string()))
+            .map(Vec::as_slice)
+    }
+
+    /// Registers the columns of a unique constraint of a table
+    pub fn add_unique_key(
+        &mut self,
+        schema: impl Into<String>,
+        table: impl Into<String>,
+        columns: Vec<String>,
+    ) {
+        self.unique_keys
+            .entry((schema.into(), table.into()))
+            .or_default()
+            .push(columns);
+    }
+
+    /// Gets the columns of every unique constraint of a table
+    pub fn get_unique_keys(&self, schema: &str, table: &str) -> &[Vec<String>] {
+        self.unique_keys
+            .get(&(schema.to_string(), table.to_string()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Registers a foreign key, indexing it for both forward and reverse lookups
+    pub fn add_foreign_key(&mut self, fk: ForeignKey) {
+        // Index by source table
+        self.foreign_keys
+            .entry((fk.from_schema.clone(), fk.from_table.clone()))
+            .or_default()
+            .push(fk.clone());
+
+        // Index by target table (reverse lookup)
+        self.reverse_fks
+            .entry((fk.to_schema.clone(), fk.to_table.clone()))
+            .or_default()
+            .push(fk);
+    }
+
+    /// Finds a foreign key relationship from one table to another.
+    ///
+    /// Returns `None` when the tables are unrelated or when the relationship is
+    /// ambiguous; use [`SchemaCache::resolve_relationship`] to tell these apart.
     pub fn find_relationship(
         &self,
         from_schema: &str,
         from_table: &str,
         to_table: &str,
     ) -> Option<Relationship> {
-        // Try forward FK (Many-to-One): from_table has FK to to_table
-        if let Some(fks) = self
-            .foreign_keys
-            .get(&(from_schema.to_string(), from_table.to_string()))
-        {
-            if let Some(fk) = fks.iter().find(|fk| fk.to_table == to_table) {
-                return Some(Relationship {
-                    from_table: from_table.to_string(),
-                    to_table: to_table.to_string(),
-                    foreign_key: fk.clone(),
-                    relation_type: RelationType::ManyToOne,
-                });
-            }
+        self.resolve_relationship(from_schema, from_table, to_table, None)
+            .ok()
+    }
+
+    /// Resolves the relationship used to embed `to_table` inside `from_table`.
+    ///
+    /// Direct foreign keys (in either direction) take precedence. Otherwise a
+    /// junction table with a key holding foreign keys to both tables yields a
+    /// many-to-many relationship (see [`SchemaCache::find_many_to_many`]). An
+    /// optional `hint` (the `!hint` of an embed) narrows the candidates, see
+    /// [`Relationship::matches_hint`]; when more than one candidate remains the
+    /// relationship is ambiguous.
+    pub fn resolve_relationship(
+        &self,
+        from_schema: &str,
+        from_table: &str,
+        to_table: &str,
+        hint: Option<&str>,
+    ) -> Result<Relationship, SqlError> {
+        let matching = |relationships: Vec<Relationship>| -> Vec<Relationship> {
+            relationships
+                .into_iter()
+                .filter(|rel| hint.is_none_or(|hint| rel.matches_hint(hint)))
+                .collect()
+        };
+
+        let mut candidates =
+            matching(self.find_direct_relationships(from_schema, from_table, to_table));
+        if candidates.is_empty() {
+            candidates = matching(self.find_many_to_many(from_schema, from_table, to_table));
         }
 
-        // Try reverse FK (One-to-Many): to_table has FK to from_table
-        if let Some(fks) = self
-            .reverse_fks
-            .get(&(from_schema.to_string(), from_table.to_string()))
-        {
-            if let Some(fk) = fks.iter().find(|fk| fk.from_table == to_table) {
-                return Some(Relationship {
-                    from_table: from_table.to_string(),
-                    to_table: to_table.to_string(),
-                    foreign_key: fk.clone(),
-                    relation_type: RelationType::OneToMany,
-                });
+        match candidates.len() {
+            0 => Err(SqlError::RelationNotFound {
+                from_table: from_table.to_string(),
+                to_table: to_table.to_string(),
+            }),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(SqlError::AmbiguousRelationship {
+                from_table: from_table.to_string(),
+                to_table: to_table.to_string(),
+                candidates: candidates
+                    .iter()
+                    .map(|rel| format!("{}!{}", to_table, rel.hint()))
+                    .collect(),
+            }),
+        }
+    }
+
+    /// Finds every relationship between two tables backed by a single foreign key:
+    /// many-to-one keys of `from_table`, then one-to-many keys referencing it
+    pub fn find_direct_relationships(
+        &self,
+        from_schema: &str,
+        from_table: &str,
+        to_table: &str,
+    ) -> Vec<Relationship> {
+        let relationship = |fk: &ForeignKey, relation_type| Relationship {
+            from_table: from_table.to_string(),
+            to_table: to_table.to_string(),
+            foreign_key: fk.clone(),
+            relation_type,
+        };
+
+        // Forward FKs (Many-to-One): from_table has FK to to_table
+        let forward = self
+            .get_foreign_keys(from_schema, from_table)
+            .into_iter()
+            .filter(|fk| fk.to_table == to_table)
+            .map(|fk| relationship(fk, RelationType::ManyToOne));
+
+        // Reverse FKs (One-to-Many): to_table has FK to from_table
+        let reverse = self
+            .get_referencing_tables(from_schema, from_table)
+            .into_iter()
+            .filter(|fk| fk.from_table == to_table)
+            .map(|fk| relationship(fk, RelationType::OneToMany));
+
+        forward.chain(reverse).collect()
+    }
+
+    /// Finds every many-to-many relationship between two tables, one per junction.
+    ///
+    /// A junction is a table with foreign keys to both tables whose columns are
+    /// all part of one of its keys: the primary key, as in PostgREST, or a unique
+    /// constraint, as with a surrogate `id` next to `UNIQUE (post_id, tag_id)`.
+    /// The target table is looked up in the source table's schema.
+    pub fn find_many_to_many(
+        &self,
+        from_schema: &str,
+        from_table: &str,
+        to_table: &str,
+    ) -> Vec<Relationship> {
+        let mut relationships = Vec::new();
+
+        // Foreign keys pointing at the source table come from junction candidates
+        for source_key in self.get_referencing_tables(from_schema, from_table) {
+            let junction = &source_key.from_table;
+            if junction == from_table || junction == to_table {
+                continue;
+            }
+            let schema = &source_key.from_schema;
+            let keys: Vec<&[String]> = self
+                .get_primary_key(schema, junction)
+                .into_iter()
+                .chain(
+                    self.get_unique_keys(schema, junction)
+                        .iter()
+                        .map(Vec::as_slice),
+                )
+                .collect();
+            let in_one_key = |target_key: &ForeignKey| {
+                keys.iter().any(|key| {
+                    source_key
+                        .from_columns
+                        .iter()
+                        .chain(&target_key.from_columns)
+                        .all(|column| key.contains(column))
+                })
+            };
+
+            for target_key in self.get_foreign_keys(schema, junction) {
+                if target_key.to_schema == from_schema
+                    && target_key.to_table == to_table
+                    && target_key != source_key
+                    && in_one_key(target_key)
+                {
+                    relationships.push(Relationship {
+                        from_table: from_table.to_string(),
+                        to_table: to_table.to_string(),
+                        foreign_key: target_key.clone(),
+                        relation_type: RelationType::ManyToMany {
+                            junction_table: junction.clone(),
+                            source_key: source_key.clone(),
+                        },
+                    });
+                }
             }
         }
 
-        // TODO: Detect Many-to-Many through junction tables
-        None
+        relationships
     }
 
     /// Gets all foreign keys from a table
@@ -204,6 +568,35 @@ impl SchemaCache {
     }
 }
 
+/// Maps `pg_proc.provolatile` (`i`, `s` or `v`) to a [`Volatility`]
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+fn volatility_from_catalog(provolatile: &str) -> Volatility {
+    match provolatile {
+        "i" => Volatility::Immutable,
+        "s" => Volatility::Stable,
+        _ => Volatility::Volatile,
+    }
+}
+
+/// Builds a [`FunctionReturn`] from the formatted return type, its
+/// `pg_type.typtype` and `pg_proc.proretset`. `record` covers `RETURNS TABLE`
+/// and `OUT` parameters, so it is composite like table row types.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+fn function_return_from_catalog(type_: String, typtype: &str, set_of: bool) -> FunctionReturn {
+    let returns = if type_ == "void" {
+        FunctionReturn::void()
+    } else if typtype == "c" || type_ == "record" {
+        FunctionReturn::composite(type_)
+    } else {
+        FunctionReturn::scalar(type_)
+    };
+    if set_of {
+        returns.set_of()
+    } else {
+        returns
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,10 +606,10 @@ mod tests {
         let fk = ForeignKey {
             from_schema: "public".to_string(),
             from_table: "orders".to_string(),
-            from_column: "customer_id".to_string(),
+            from_columns: vec!["customer_id".to_string()],
             to_schema: "public".to_string(),
             to_table: "customers".to_string(),
-            to_column: "id".to_string(),
+            to_columns: vec!["id".to_string()],
             constraint_name: "orders_customer_id_fkey".to_string(),
         };
 
@@ -229,10 +622,10 @@ mod tests {
         let fk = ForeignKey {
             from_schema: "public".to_string(),
             from_table: "orders".to_string(),
-            from_column: "customer_id".to_string(),
+            from_columns: vec!["customer_id".to_string()],
             to_schema: "public".to_string(),
             to_table: "customers".to_string(),
-            to_column: "id".to_string(),
+            to_columns: vec!["id".to_string()],
             constraint_name: "orders_customer_id_fkey".to_string(),
         };
 
@@ -240,9 +633,338 @@ mod tests {
         assert_eq!(condition, r#""orders"."customer_id" = "customers"."id""#);
     }
 
+    #[test]
+    fn test_join_condition_composite() {
+        let fk = ForeignKey {
+            from_schema: "public".to_string(),
+            from_table: "tenant_tasks".to_string(),
+            from_columns: vec!["tenant_id".to_string(), "project_id".to_string()],
+            to_schema: "public".to_string(),
+            to_table: "tenant_projects".to_string(),
+            to_columns: vec!["tenant_id".to_string(), "id".to_string()],
+            constraint_name: "tenant_tasks_tenant_id_project_id_fkey".to_string(),
+        };
+
+        let condition = fk.join_condition("tenant_tasks", "tenant_projects");
+        assert_eq!(
+            condition,
+            r#""tenant_tasks"."tenant_id" = "tenant_projects"."tenant_id" AND "tenant_tasks"."project_id" = "tenant_projects"."id""#
+        );
+    }
+
     #[test]
     fn test_schema_cache_empty() {
         let cache = SchemaCache::new();
         assert_eq!(cache.get_foreign_keys("public", "users").len(), 0);
     }
+
+    #[test]
+    fn test_primary_keys() {
+        let mut cache = SchemaCache::new();
+        cache.add_primary_key(
+            "public",
+            "tenant_projects",
+            vec!["tenant_id".to_string(), "id".to_string()],
+        );
+
+        assert_eq!(
+            cache.get_primary_key("public", "tenant_projects"),
+            Some(&["tenant_id".to_string(), "id".to_string()][..])
+        );
+        assert_eq!(cache.get_primary_key("auth", "tenant_projects"), None);
+    }
+
+    #[test]
+    fn test_add_foreign_key_indexes_both_directions() {
+        let mut cache = SchemaCache::new();
+        cache.add_foreign_key(ForeignKey {
+            from_schema: "public".to_string(),
+            from_table: "orders".to_string(),
+            from_columns: vec!["customer_id".to_string()],
+            to_schema: "public".to_string(),
+            to_table: "customers".to_string(),
+            to_columns: vec!["id".to_string()],
+            constraint_name: "orders_customer_id_fkey".to_string(),
+        });
+
+        assert_eq!(cache.get_foreign_keys("public", "orders").len(), 1);
+        assert_eq!(cache.get_referencing_tables("public", "customers").len(), 1);
+        assert!(cache
+            .find_relationship("public", "customers", "orders")
+            .is_some());
+    }
+
+    fn fk(from_table: &str, from_column: &str, to_table: &str) -> ForeignKey {
+        ForeignKey {
+            from_schema: "public".to_string(),
+            from_table: from_table.to_string(),
+            from_columns: vec![from_column.to_string()],
+            to_schema: "public".to_string(),
+            to_table: to_table.to_string(),
+            to_columns: vec!["id".to_string()],
+            constraint_name: format!("{}_{}_fkey", from_table, from_column),
+        }
+    }
+
+    /// Adds a posts-tags junction, keyed by both of its foreign keys
+    fn add_junction(cache: &mut SchemaCache, junction: &str) {
+        cache.add_foreign_key(fk(junction, "post_id", "posts"));
+        cache.add_foreign_key(fk(junction, "tag_id", "tags"));
+        cache.add_primary_key(
+            "public",
+            junction,
+            vec!["post_id".to_string(), "tag_id".to_string()],
+        );
+    }
+
+    #[test]
+    fn test_resolve_many_to_many_through_junction() {
+        let mut cache = SchemaCache::new();
+        add_junction(&mut cache, "post_tags");
+
+        let rel = cache
+            .resolve_relationship("public", "posts", "tags", None)
+            .unwrap();
+        assert_eq!(rel.foreign_key.from_columns, vec!["tag_id"]);
+        match rel.relation_type {
+            RelationType::ManyToMany {
+                junction_table,
+                source_key,
+            } => {
+                assert_eq!(junction_table, "post_tags");
+                assert_eq!(source_key.from_columns, vec!["post_id"]);
+            }
+            other => panic!("Expected many-to-many, got {:?}", other),
+        }
+
+        // The junction itself is still reachable directly
+        let rel = cache
+            .resolve_relationship("public", "posts", "post_tags", None)
+            .unwrap();
+        assert_eq!(rel.relation_type, RelationType::OneToMany);
+    }
+
+    #[test]
+    fn test_tables_with_two_foreign_keys_are_not_junctions() {
+        let mut cache = SchemaCache::new();
+        add_junction(&mut cache, "post_tags");
+        // Its own primary key, not made of the foreign keys
+        cache.add_foreign_key(fk("post_reviews", "post_id", "posts"));
+        cache.add_foreign_key(fk("post_reviews", "tag_id", "tags"));
+        cache.add_primary_key("public", "post_reviews", vec!["id".to_string()]);
+        // Each foreign key unique on its own, not as a pair
+        cache.add_foreign_key(fk("post_covers", "post_id", "posts"));
+        cache.add_foreign_key(fk("post_covers", "tag_id", "tags"));
+        cache.add_unique_key("public", "post_covers", vec!["post_id".to_string()]);
+        cache.add_unique_key("public", "post_covers", vec!["tag_id".to_string()]);
+        // No primary key at all
+        cache.add_foreign_key(fk("post_log", "post_id", "posts"));
+        cache.add_foreign_key(fk("post_log", "tag_id", "tags"));
+
+        let rel = cache
+            .resolve_relationship("public", "posts", "tags", None)
+            .unwrap();
+        assert!(matches!(
+            rel.relation_type,
+            RelationType::ManyToMany { ref junction_table, .. } if junction_table == "post_tags"
+        ));
+    }
+
+    #[test]
+    fn test_junction_with_surrogate_id_and_unique_foreign_keys() {
+        let mut cache = SchemaCache::new();
+        cache.add_foreign_key(fk("post_tags", "post_id", "posts"));
+        cache.add_foreign_key(fk("post_tags", "tag_id", "tags"));
+        cache.add_primary_key("public", "post_tags", vec!["id".to_string()]);
+        cache.add_unique_key(
+            "public",
+            "post_tags",
+            vec!["post_id".to_string(), "tag_id".to_string()],
+        );
+
+        let rel = cache
+            .resolve_relationship("public", "posts", "tags", None)
+            .unwrap();
+        assert!(matches!(
+            rel.relation_type,
+            RelationType::ManyToMany { ref junction_table, .. } if junction_table == "post_tags"
+        ));
+    }
+
+    #[test]
+    fn test_many_to_many_target_in_source_schema() {
+        let mut cache = SchemaCache::new();
+        add_junction(&mut cache, "post_tags");
+        let mut archived = fk("post_tags", "tag_id", "tags");
+        archived.to_schema = "archive".to_string();
+        archived.constraint_name = "post_tags_archived_tag_id_fkey".to_string();
+        cache.add_foreign_key(archived);
+
+        // Only the key to public.tags is a candidate, so there is no ambiguity
+        let rel = cache
+            .resolve_relationship("public", "posts", "tags", None)
+            .unwrap();
+        assert_eq!(rel.foreign_key.to_schema, "public");
+    }
+
+    #[test]
+    fn test_resolve_many_to_many_ambiguous() {
+        let mut cache = SchemaCache::new();
+        add_junction(&mut cache, "post_tags");
+        add_junction(&mut cache, "featured_tags");
+
+        let err = cache
+            .resolve_relationship("public", "posts", "tags", None)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SqlError::AmbiguousRelationship {
+                from_table: "posts".to_string(),
+                to_table: "tags".to_string(),
+                candidates: vec![
+                    "tags!post_tags".to_string(),
+                    "tags!featured_tags".to_string()
+                ],
+            }
+        );
+        assert!(cache.find_relationship("public", "posts", "tags").is_none());
+    }
+
+    #[test]
+    fn test_resolve_relationship_not_found() {
+        let cache = SchemaCache::new();
+        let err = cache
+            .resolve_relationship("public", "posts", "tags", None)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SqlError::RelationNotFound {
+                from_table: "posts".to_string(),
+                to_table: "tags".to_string(),
+            }
+        );
+    }
+
+    fn addresses_cache() -> SchemaCache {
+        let mut cache = SchemaCache::new();
+        cache.add_foreign_key(fk("orders", "billing_address_id", "addresses"));
+        cache.add_foreign_key(fk("orders", "shipping_address_id", "addresses"));
+        cache
+    }
+
+    #[test]
+    fn test_resolve_ambiguous_foreign_keys() {
+        let err = addresses_cache()
+            .resolve_relationship("public", "orders", "addresses", None)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SqlError::AmbiguousRelationship {
+                from_table: "orders".to_string(),
+                to_table: "addresses".to_string(),
+                candidates: vec![
+                    "addresses!orders_billing_address_id_fkey".to_string(),
+                    "addresses!orders_shipping_address_id_fkey".to_string(),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolve_hint_by_constraint_name() {
+        let rel = addresses_cache()
+            .resolve_relationship(
+                "public",
+                "orders",
+                "addresses",
+                Some("orders_shipping_address_id_fkey"),
+            )
+            .unwrap();
+        assert_eq!(rel.foreign_key.from_columns, vec!["shipping_address_id"]);
+        assert_eq!(rel.relation_type, RelationType::ManyToOne);
+    }
+
+    #[test]
+    fn test_resolve_hint_by_column() {
+        let cache = addresses_cache();
+        let rel = cache
+            .resolve_relationship("public", "orders", "addresses", Some("billing_address_id"))
+            .unwrap();
+        assert_eq!(rel.foreign_key.from_columns, vec!["billing_address_id"]);
+
+        // The referencing column also selects the reverse (one-to-many) direction
+        let rel = cache
+            .resolve_relationship("public", "addresses", "orders", Some("billing_address_id"))
+            .unwrap();
+        assert_eq!(rel.relation_type, RelationType::OneToMany);
+        assert_eq!(rel.foreign_key.from_columns, vec!["billing_address_id"]);
+    }
+
+    #[test]
+    fn test_resolve_hint_matching_nothing() {
+        let err = addresses_cache()
+            .resolve_relationship("public", "orders", "addresses", Some("missing_fkey"))
+            .unwrap_err();
+        assert!(matches!(err, SqlError::RelationNotFound { .. }));
+    }
+
+    #[test]
+    fn test_resolve_many_to_many_hint_by_junction() {
+        let mut cache = SchemaCache::new();
+        add_junction(&mut cache, "post_tags");
+        add_junction(&mut cache, "featured_tags");
+
+        let rel = cache
+            .resolve_relationship("public", "posts", "tags", Some("featured_tags"))
+            .unwrap();
+        assert!(matches!(
+            rel.relation_type,
+            RelationType::ManyToMany { ref junction_table, .. } if junction_table == "featured_tags"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_self_reference_needs_hint() {
+        let mut cache = SchemaCache::new();
+        cache.add_foreign_key(fk("employees", "manager_id", "employees"));
+
+        let err = cache
+            .resolve_relationship("public", "employees", "employees", None)
+            .unwrap_err();
+        assert!(matches!(err, SqlError::AmbiguousRelationship { .. }));
+    }
+
+    #[test]
+    fn test_functions_with_overloads() {
+        use crate::ast::FunctionParam;
+
+        let mut cache = SchemaCache::new();
+        cache.add_function(
+            Function::new("public", "search").with_params(vec![FunctionParam::new("term", "text")]),
+        );
+        cache.add_function(Function::new("public", "search").with_params(vec![
+            FunctionParam::new("term", "text"),
+            FunctionParam::new("max_results", "integer"),
+        ]));
+
+        assert_eq!(cache.get_functions("public", "search").len(), 2);
+        assert!(cache.get_functions("api", "search").is_empty());
+    }
+
+    #[test]
+    fn test_function_metadata_from_catalog() {
+        use crate::ast::ReturnKind;
+
+        assert_eq!(volatility_from_catalog("s"), Volatility::Stable);
+        assert_eq!(volatility_from_catalog("v"), Volatility::Volatile);
+
+        let returns = function_return_from_catalog("products".to_string(), "c", true);
+        assert_eq!(returns, FunctionReturn::composite("products").set_of());
+        let returns = function_return_from_catalog("record".to_string(), "p", true);
+        assert_eq!(returns.kind, ReturnKind::Composite);
+        let returns = function_return_from_catalog("integer".to_string(), "b", false);
+        assert_eq!(returns, FunctionReturn::scalar("integer"));
+        let returns = function_return_from_catalog("void".to_string(), "p", false);
+        assert_eq!(returns.kind, ReturnKind::Void);
+    }
 }
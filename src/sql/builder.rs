@@ -1,5 +1,7 @@
+use super::fragment::{Ident, Literal, TypeName};
 use crate::ast::*;
 use crate::error::SqlError;
+use std::collections::BTreeMap;
 
 /// Result of building a SQL query with parameterized values.
 ///
@@ -8,8 +10,9 @@ use crate::error::SqlError;
 ///
 /// # SQL Injection Prevention
 ///
-/// All user input is passed via parameters, never interpolated into SQL strings.
-/// This provides complete protection against SQL injection attacks.
+/// All user-supplied values are passed via parameters, never interpolated into SQL
+/// strings. Identifiers, JSON keys, casts and text search configurations, which
+/// cannot be parameters, are escaped or validated by [`crate::sql::fragment`].
 ///
 /// # Examples
 ///
@@ -40,6 +43,105 @@ pub struct QueryResult {
     pub params: Vec<serde_json::Value>,
     /// List of table names referenced in the query
     pub tables: Vec<String>,
+    /// Companion queries answering `Prefer: count=...`, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<CountQuery>,
+    /// Set when the query is wrapped into a single response row (see
+    /// [`QueryBuilder::with_wrapped_response`]), to the plurality of its body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrapped: Option<Plurality>,
+    /// Preferences honored by the query, for the `Preference-Applied` header
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub preference_applied: Vec<String>,
+}
+
+impl QueryResult {
+    /// Checks the `page_total` column returned by a wrapped query.
+    ///
+    /// A singular response must match exactly one row; PostgREST answers anything
+    /// else with `406 Not Acceptable`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqlError::SingularRowCount`] when a singular response matched
+    /// zero or several rows.
+    pub fn check_page_total(&self, page_total: i64) -> Result<(), SqlError> {
+        match self.wrapped {
+            Some(Plurality::Singular) if page_total != 1 => {
+                Err(SqlError::SingularRowCount(page_total))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Default `count=estimated` threshold: planner estimates up to this many rows
+/// are replaced by an exact count
+pub const DEFAULT_ESTIMATED_COUNT_THRESHOLD: u64 = 1000;
+
+/// Queries computing the total number of rows a SELECT matches, ignoring its
+/// limit and offset, for the `Content-Range` header.
+///
+/// - `count=exact` builds only [`exact`](Self::exact)
+/// - `count=planned` builds only [`planned`](Self::planned)
+/// - `count=estimated` builds both: run the planned query first and only run the
+///   exact one when [`needs_exact`](Self::needs_exact) says the estimate is small
+///
+/// # Examples
+///
+/// ```
+/// use postgrest_parser::{parse, operation_to_sql, CountQuery};
+/// use std::collections::HashMap;
+///
+/// let headers = HashMap::from([("Prefer".to_string(), "count=exact".to_string())]);
+/// let op = parse("GET", "users", "age=gte.18&limit=10", None, Some(&headers)).unwrap();
+/// let result = operation_to_sql("users", &op).unwrap();
+///
+/// let count = result.count.unwrap();
+/// assert_eq!(
+///     count.exact.unwrap(),
+///     r#"SELECT pg_catalog.count(*) FROM (SELECT * FROM "users" WHERE "age" >= $1) AS "pgrst_source""#
+/// );
+/// // The limit parameter is not used by the count query
+/// assert_eq!(count.params.len(), 1);
+///
+/// let explain = serde_json::json!([{"Plan": {"Node Type": "Seq Scan", "Plan Rows": 42}}]);
+/// assert_eq!(CountQuery::planned_rows(&explain), Some(42));
+/// ```
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountQuery {
+    /// The requested count strategy
+    pub strategy: Count,
+    /// `SELECT pg_catalog.count(*) FROM (...)`, returning the exact total
+    pub exact: Option<String>,
+    /// `EXPLAIN (FORMAT JSON) ...`, whose output holds the planner's row estimate
+    /// (see [`CountQuery::planned_rows`])
+    pub planned: Option<String>,
+    /// For `count=estimated`, the estimate above which the exact count is skipped
+    pub threshold: Option<u64>,
+    /// Parameters shared by the count queries, a prefix of the main query's parameters
+    pub params: Vec<serde_json::Value>,
+}
+
+impl CountQuery {
+    /// Extracts the row estimate from the JSON output of the planned query
+    pub fn planned_rows(explain: &serde_json::Value) -> Option<u64> {
+        let rows = explain.get(0)?.get("Plan")?.get("Plan Rows")?;
+        rows.as_u64()
+            .or_else(|| rows.as_f64().map(|rows| rows as u64))
+    }
+
+    /// Returns true when the exact query must run given the planner's estimate
+    pub fn needs_exact(&self, planned_rows: u64) -> bool {
+        match self.strategy {
+            Count::Exact => true,
+            Count::Planned => false,
+            Count::Estimated => {
+                planned_rows <= self.threshold.unwrap_or(DEFAULT_ESTIMATED_COUNT_THRESHOLD)
+            }
+        }
+    }
 }
 
 /// Builder for generating parameterized PostgreSQL SQL queries.
@@ -74,6 +176,62 @@ pub struct QueryBuilder {
     pub schema_cache: Option<std::sync::Arc<crate::schema_cache::SchemaCache>>,
     /// Current schema being queried (for relation resolution)
     pub current_schema: String,
+    /// Table aliases already used by embedded subqueries
+    #[cfg(feature = "postgres")]
+    pub(crate) aliases: Vec<String>,
+    /// Count strategy requested with `Prefer: count=...`
+    pub count: Option<Count>,
+    /// Threshold used by `count=estimated`
+    pub estimated_count_threshold: u64,
+    /// Shape of the single response row SELECTs are wrapped into, if any
+    pub wrap: Option<Plurality>,
+    /// What mutations return, from `Prefer: return=...`
+    pub return_representation: Option<ReturnRepresentation>,
+    /// How INSERT fills columns a row has no value for, from `Prefer: missing=...`
+    pub missing: Option<Missing>,
+    /// How INSERT handles rows conflicting with existing ones, from `Prefer: resolution=...`
+    pub resolution: Option<Resolution>,
+}
+
+/// One entry of a select list
+struct SelectColumn {
+    sql: String,
+    /// `SELECT 1 ...` query matching the rows of an embedded resource, used for
+    /// `!inner` and `rel=is.null` filtering
+    exists: Option<String>,
+    /// `LEFT JOIN LATERAL` clause a spread relation adds to the FROM clause
+    lateral: Option<String>,
+    /// What a level with aggregates must be grouped by for this column to stay
+    /// valid: the parent columns an embed is correlated on, or the columns a
+    /// spread lifts into the row. None when they are not known.
+    group_by: Option<Vec<String>>,
+}
+
+impl SelectColumn {
+    fn new(sql: String) -> Self {
+        Self {
+            sql,
+            exists: None,
+            lateral: None,
+            group_by: Some(Vec::new()),
+        }
+    }
+}
+
+/// Concatenates the lateral joins required by the spread relations of a select list
+fn lateral_joins(columns: &[SelectColumn]) -> String {
+    columns
+        .iter()
+        .filter_map(|column| column.lateral.as_deref())
+        .collect()
+}
+
+/// A table reference that an embedded subquery is correlated with
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+struct EmbedScope {
+    schema: String,
+    table: String,
+    alias: String,
 }
 
 impl Default for QueryBuilder {
@@ -93,6 +251,14 @@ impl QueryBuilder {
             #[cfg(feature = "postgres")]
             schema_cache: None,
             current_schema: "public".to_string(),
+            #[cfg(feature = "postgres")]
+            aliases: Vec::new(),
+            count: None,
+            estimated_count_threshold: DEFAULT_ESTIMATED_COUNT_THRESHOLD,
+            wrap: None,
+            return_representation: None,
+            missing: None,
+            resolution: None,
         }
     }
 
@@ -112,6 +278,101 @@ impl QueryBuilder {
         self
     }
 
+    /// Requests companion count queries for SELECTs (see [`CountQuery`])
+    pub fn with_count(mut self, count: Count) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Sets the `count=estimated` threshold
+    pub fn with_estimated_count_threshold(mut self, threshold: u64) -> Self {
+        self.estimated_count_threshold = threshold;
+        self
+    }
+
+    /// Wraps SELECTs into PostgREST's response shape: one row holding the whole
+    /// response body as JSON, built in a single round trip.
+    ///
+    /// The wrapped query returns three columns:
+    ///
+    /// - `total_result_set`: the total number of matching rows when `count=exact`
+    ///   was requested (see [`with_count`](Self::with_count)), NULL otherwise
+    /// - `page_total`: the number of rows in the body
+    /// - `body`: a JSON array of the rows for [`Plurality::Multiple`], or the
+    ///   only row as a JSON object for [`Plurality::Singular`] (check
+    ///   `page_total` with [`QueryResult::check_page_total`])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use postgrest_parser::{parse_query_string, Plurality, QueryBuilder};
+    ///
+    /// let params = parse_query_string("select=id,name&limit=10").unwrap();
+    /// let result = QueryBuilder::new()
+    ///     .with_wrapped_response(Plurality::Multiple)
+    ///     .build_select("users", &params)
+    ///     .unwrap();
+    ///
+    /// assert!(result.query.starts_with(r#"WITH "pgrst_source" AS (SELECT "id", "name" FROM "users" LIMIT $1)"#));
+    /// assert!(result.query.contains(r#"coalesce(json_agg("_postgrest_t"), '[]') AS "body""#));
+    /// ```
+    pub fn with_wrapped_response(mut self, plurality: Plurality) -> Self {
+        self.wrap = Some(plurality);
+        self
+    }
+
+    /// Sets what INSERT, UPDATE and DELETE return (`Prefer: return=...`):
+    ///
+    /// - [`ReturnRepresentation::Full`]: the `select` list, or every column
+    /// - [`ReturnRepresentation::Minimal`]: nothing, even with a `select` list
+    /// - [`ReturnRepresentation::HeadersOnly`]: the primary key columns, to build
+    ///   a `Location` header; nothing when the schema cache does not know the key
+    ///
+    /// Without it, mutations return the `select` list when there is one.
+    pub fn with_return(mut self, representation: ReturnRepresentation) -> Self {
+        self.return_representation = Some(representation);
+        self
+    }
+
+    /// Sets how INSERT fills the columns a row has no key for (`Prefer: missing=...`):
+    /// NULL by default, or the column default with [`Missing::Default`].
+    ///
+    /// Columns come from `columns=` or, for bulk inserts, the keys of every row,
+    /// so rows may leave some of them out.
+    pub fn with_missing(mut self, missing: Missing) -> Self {
+        self.missing = Some(missing);
+        self
+    }
+
+    /// Sets how INSERT handles duplicates (`Prefer: resolution=...`), as an
+    /// `ON CONFLICT` clause on the table's primary key from the schema cache:
+    ///
+    /// - [`Resolution::MergeDuplicates`]: `DO UPDATE SET` every inserted column
+    ///   outside the conflict target
+    /// - [`Resolution::IgnoreDuplicates`]: `DO NOTHING`
+    ///
+    /// `on_conflict=` replaces the primary key as the conflict target. Without
+    /// either, the preference is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use postgrest_parser::{parse_insert_params, QueryBuilder, Resolution, ResolvedTable};
+    ///
+    /// let params = parse_insert_params("on_conflict=email", r#"{"email": "a@b.c", "name": "A"}"#).unwrap();
+    /// let result = QueryBuilder::new()
+    ///     .with_resolution(Resolution::MergeDuplicates)
+    ///     .build_insert(&ResolvedTable::new("public", "users"), &params)
+    ///     .unwrap();
+    ///
+    /// assert!(result.query.ends_with(r#"ON CONFLICT ("email") DO UPDATE SET "name" = EXCLUDED."name""#));
+    /// assert_eq!(result.preference_applied, vec!["resolution=merge-duplicates"]);
+    /// ```
+    pub fn with_resolution(mut self, resolution: Resolution) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
+
     /// Builds a SELECT query from parsed parameters.
     ///
     /// # Examples
@@ -136,163 +397,573 @@ impl QueryBuilder {
     ) -> Result<QueryResult, SqlError> {
         self.tables.push(table.to_string());
 
-        if let Some(select) = &params.select {
-            self.build_select_clause(select)?;
+        let (joins, conditions, group_by) = if let Some(select) = &params.select {
+            self.build_select_list(select, &params.embedded, &params.filters)?
+        } else if let Some(name) = params.embedded.keys().next() {
+            return Err(SqlError::EmbedNotSelected(name.clone()));
         } else {
             self.sql.push_str("SELECT *");
-        }
+            let conditions = self.build_level_conditions(&[], &[], &params.filters, None)?;
+            (String::new(), conditions, String::new())
+        };
 
         self.build_from_clause(table)?;
+        self.sql.push_str(&joins);
 
-        if !params.filters.is_empty() {
-            self.build_where_clause(&params.filters)?;
+        if !conditions.is_empty() {
+            self.sql.push_str(" WHERE ");
+            self.sql.push_str(&conditions.join(" AND "));
         }
 
+        self.sql.push_str(&group_by);
+
+        // Everything so far selects the matching rows; ordering and pagination follow
+        let count = self.count.map(|strategy| self.count_query(strategy));
+        let matching_len = self.sql.len();
+
         if !params.order.is_empty() {
-            self.build_order_clause(&params.order)?;
+            let items = params.select.as_deref().unwrap_or(&[]);
+            let root = self.root_scope()?;
+            let order_sql = self.order_by_sql(&params.order, items, None, Some(&root))?;
+            self.sql.push_str(&order_sql);
         }
 
         self.build_limit_offset(params.limit, params.offset)?;
 
+        if let Some(plurality) = self.wrap {
+            self.wrap_response(plurality, matching_len);
+        }
+
         Ok(QueryResult {
             query: self.sql.clone(),
             params: self.params.clone(),
             tables: self.tables.clone(),
+            count,
+            wrapped: self.wrap,
+            preference_applied: Vec::new(),
         })
     }
 
+    /// Wraps the SELECT built so far into a single response row; the first
+    /// `matching_len` bytes of it select all matching rows, before pagination
+    fn wrap_response(&mut self, plurality: Plurality, matching_len: usize) {
+        let source = self.quote_identifier("pgrst_source");
+        let row = self.quote_identifier("_postgrest_t");
+
+        let total = match self.count {
+            Some(Count::Exact) => format!(
+                "(SELECT pg_catalog.count(*) FROM ({}) AS {})",
+                &self.sql[..matching_len],
+                self.quote_identifier("pgrst_source_count")
+            ),
+            _ => "NULL::bigint".to_string(),
+        };
+        let body = match plurality {
+            Plurality::Multiple => format!("coalesce(json_agg({row}), '[]')"),
+            Plurality::Singular => format!("coalesce(json_agg({row})->0, 'null')"),
+        };
+
+        self.sql = format!(
+            "WITH {source} AS ({}) SELECT {total} AS {}, pg_catalog.count({row}) AS {}, {body} AS {} FROM (SELECT * FROM {source}) AS {row}",
+            self.sql,
+            self.quote_identifier("total_result_set"),
+            self.quote_identifier("page_total"),
+            self.quote_identifier("body"),
+        );
+    }
+
+    /// Builds the count queries for the rows selected by the SQL built so far
+    fn count_query(&self, strategy: Count) -> CountQuery {
+        let exact = format!(
+            "SELECT pg_catalog.count(*) FROM ({}) AS {}",
+            self.sql,
+            self.quote_identifier("pgrst_source")
+        );
+        let planned = format!("EXPLAIN (FORMAT JSON) {}", self.sql);
+
+        let (exact, planned, threshold) = match strategy {
+            Count::Exact => (Some(exact), None, None),
+            Count::Planned => (None, Some(planned), None),
+            Count::Estimated => (
+                Some(exact),
+                Some(planned),
+                Some(self.estimated_count_threshold),
+            ),
+        };
+
+        CountQuery {
+            strategy,
+            exact,
+            planned,
+            threshold,
+            params: self.params.clone(),
+        }
+    }
+
     pub(crate) fn build_select_clause(&mut self, items: &[SelectItem]) -> Result<(), SqlError> {
+        self.build_select_list(items, &BTreeMap::new(), &[])?;
+        Ok(())
+    }
+
+    /// Builds the root SELECT list, applying `embedded` parameters to the
+    /// subqueries of the relations they name.
+    ///
+    /// Returns the lateral joins spread relations add to the root FROM clause,
+    /// the root WHERE conditions (`filters` plus the existence checks required by
+    /// `!inner` embeds and `rel=is.null` filters) and the root GROUP BY.
+    pub(crate) fn build_select_list(
+        &mut self,
+        items: &[SelectItem],
+        embedded: &BTreeMap<String, EmbedParams>,
+        filters: &[LogicCondition],
+    ) -> Result<(String, Vec<String>, String), SqlError> {
         if items.is_empty() {
             return Err(SqlError::NoSelectItems);
         }
+        check_embedded_selected(items, embedded)?;
 
-        let columns: Vec<String> = items
+        let columns: Vec<SelectColumn> = items
             .iter()
-            .map(|item| self.select_item_to_sql(item))
+            .map(|item| self.select_item_to_sql(item, find_embedded(embedded, item)))
             .collect::<Result<Vec<_>, _>>()?;
+        let column_sql: Vec<&str> = columns.iter().map(|column| column.sql.as_str()).collect();
         self.sql.push_str("SELECT ");
-        self.sql.push_str(&columns.join(", "));
-        Ok(())
+        self.sql.push_str(&column_sql.join(", "));
+
+        let conditions = self.build_level_conditions(items, &columns, filters, None)?;
+        let group_by = self.group_by_sql(items, &columns, None)?;
+        Ok((lateral_joins(&columns), conditions, group_by))
     }
 
-    fn select_item_to_sql(&self, item: &SelectItem) -> Result<String, SqlError> {
+    fn select_item_to_sql(
+        &mut self,
+        item: &SelectItem,
+        embed_params: Option<&EmbedParams>,
+    ) -> Result<SelectColumn, SqlError> {
         match item.item_type {
-            ItemType::Field => {
-                if item.name == "*" {
-                    Ok("*".to_string())
-                } else {
-                    let field_sql = self.field_to_sql(&Field::new(&item.name));
-                    if let Some(alias) = &item.alias {
-                        Ok(format!("{} AS {}", field_sql, self.quote_identifier(alias)))
-                    } else {
-                        Ok(field_sql)
-                    }
+            ItemType::Field => Ok(SelectColumn::new(self.select_field_to_sql(None, item)?)),
+            ItemType::Relation | ItemType::Spread => self.build_relation_sql(item, embed_params),
+        }
+    }
+
+    /// Builds a select-list column, qualified with `qualifier` when given.
+    ///
+    /// Aggregates and columns with a JSON path or cast are always named, by their
+    /// alias or PostgREST's default (see [`SelectItem::output_name`]).
+    pub(crate) fn select_field_to_sql(
+        &self,
+        qualifier: Option<&str>,
+        item: &SelectItem,
+    ) -> Result<String, SqlError> {
+        if let Some(aggregate) = &item.aggregate {
+            let argument = if item.name == "*" {
+                "*".to_string()
+            } else {
+                self.select_column_expression(qualifier, item)?
+            };
+            let cast = match &aggregate.cast {
+                Some(cast) => format!("::{}", TypeName::parse(cast)?),
+                None => String::new(),
+            };
+            return Ok(format!(
+                "{}({}){} AS {}",
+                aggregate.function.name(),
+                argument,
+                cast,
+                self.quote_identifier(item.output_name())
+            ));
+        }
+
+        if item.name == "*" {
+            return Ok(match qualifier {
+                Some(qualifier) => format!("{}.*", self.quote_identifier(qualifier)),
+                None => "*".to_string(),
+            });
+        }
+
+        let column = self.select_column_expression(qualifier, item)?;
+
+        if item.alias.is_some() || item.field.is_some() {
+            Ok(format!(
+                "{} AS {}",
+                column,
+                self.quote_identifier(item.output_name())
+            ))
+        } else {
+            Ok(column)
+        }
+    }
+
+    /// The column expression of a field item, without its output name
+    fn select_column_expression(
+        &self,
+        qualifier: Option<&str>,
+        item: &SelectItem,
+    ) -> Result<String, SqlError> {
+        match &item.field {
+            Some(field) => self.qualified_field_to_sql(qualifier, field),
+            None => self.qualified_field_to_sql(qualifier, &Field::new(&item.name)),
+        }
+    }
+
+    /// Builds the implicit GROUP BY of a select level: when any column is an
+    /// aggregate, the level is grouped by its remaining plain columns, the
+    /// parent columns its embeds are correlated on and the columns its spreads
+    /// lift into the row (see [`SelectColumn::group_by`]).
+    fn group_by_sql(
+        &self,
+        items: &[SelectItem],
+        columns: &[SelectColumn],
+        qualifier: Option<&str>,
+    ) -> Result<String, SqlError> {
+        if !items.iter().any(SelectItem::is_aggregate) {
+            return Ok(String::new());
+        }
+
+        let mut group_by = items
+            .iter()
+            .filter(|item| {
+                item.item_type == ItemType::Field && !item.is_aggregate() && item.name != "*"
+            })
+            .map(|item| self.select_column_expression(qualifier, item))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (item, column) in items.iter().zip(columns) {
+            let expressions = column
+                .group_by
+                .as_ref()
+                .ok_or_else(|| SqlError::UngroupableRelation(item.name.clone()))?;
+            for expression in expressions {
+                if !group_by.contains(expression) {
+                    group_by.push(expression.clone());
                 }
             }
-            ItemType::Relation | ItemType::Spread => self.build_relation_sql(item),
         }
-    }
 
-    fn build_relation_sql(&self, item: &SelectItem) -> Result<String, SqlError> {
-        #[cfg(feature = "postgres")]
-        let rel_table = &item.name;
+        if group_by.is_empty() {
+            Ok(String::new())
+        } else {
+            Ok(format!(" GROUP BY {}", group_by.join(", ")))
+        }
+    }
 
+    #[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+    fn build_relation_sql(
+        &mut self,
+        item: &SelectItem,
+        embed_params: Option<&EmbedParams>,
+    ) -> Result<SelectColumn, SqlError> {
         #[cfg(feature = "postgres")]
         {
-            // With schema cache: generate proper JOINs
-            if let Some(cache) = &self.schema_cache {
-                // Get current table (last in tables vec)
-                let current_table = self.tables.last().ok_or(SqlError::NoTableContext)?;
-
-                // Find relationship
-                if let Some(rel) =
-                    cache.find_relationship(&self.current_schema, current_table, rel_table)
-                {
-                    return self.build_relation_with_fk(item, &rel);
-                } else {
-                    // No relationship found - return error with helpful message
-                    return Err(SqlError::RelationNotFound {
-                        from_table: current_table.clone(),
-                        to_table: rel_table.clone(),
-                    });
-                }
+            // With schema cache: generate correlated subqueries
+            if let Some(cache) = self.schema_cache.clone() {
+                // Top-level relations are correlated with the root table
+                let parent = self.root_scope()?;
+                return self.build_embed(&cache, item, &parent, embed_params);
             }
         }
 
         // Without schema cache: generate placeholder (won't work!)
         // This maintains backward compatibility but produces invalid SQL
-        self.build_relation_placeholder(item)
+        self.build_relation_placeholder(item).map(SelectColumn::new)
+    }
+
+    /// The root table of the query, which top-level embeds are correlated with
+    fn root_scope(&mut self) -> Result<EmbedScope, SqlError> {
+        let root_table = self.tables.first().ok_or(SqlError::NoTableContext)?.clone();
+        #[cfg(feature = "postgres")]
+        if !self.aliases.contains(&root_table) {
+            self.aliases.push(root_table.clone());
+        }
+
+        Ok(EmbedScope {
+            schema: self.current_schema.clone(),
+            alias: root_table.clone(),
+            table: root_table,
+        })
+    }
+
+    #[cfg(feature = "postgres")]
+    fn build_embed(
+        &mut self,
+        cache: &crate::schema_cache::SchemaCache,
+        item: &SelectItem,
+        parent: &EmbedScope,
+        embed_params: Option<&EmbedParams>,
+    ) -> Result<SelectColumn, SqlError> {
+        let rel = cache.resolve_relationship(
+            &parent.schema,
+            &parent.table,
+            &item.name,
+            item.relation_hint(),
+        )?;
+
+        self.build_relation_with_fk(cache, item, &rel, parent, embed_params)
     }
 
     #[cfg(feature = "postgres")]
     fn build_relation_with_fk(
-        &self,
+        &mut self,
+        cache: &crate::schema_cache::SchemaCache,
         item: &SelectItem,
         rel: &crate::schema_cache::Relationship,
-    ) -> Result<String, SqlError> {
+        parent: &EmbedScope,
+        embed_params: Option<&EmbedParams>,
+    ) -> Result<SelectColumn, SqlError> {
         use crate::schema_cache::RelationType;
 
-        let rel_table = &item.name;
-        let current_table = self.tables.last().unwrap();
+        // Spreading lifts columns into the parent row, which needs at most one
+        // match, or aggregates computing a single row over the related rows
+        if item.item_type == ItemType::Spread
+            && rel.relation_type != RelationType::ManyToOne
+            && !is_aggregate_spread(item)
+        {
+            return Err(SqlError::SpreadToMany(item.name.clone()));
+        }
+
+        let fk = &rel.foreign_key;
+        let schema = match rel.relation_type {
+            RelationType::OneToMany => fk.from_schema.clone(),
+            RelationType::ManyToOne | RelationType::ManyToMany { .. } => fk.to_schema.clone(),
+        };
+
+        let target = EmbedScope {
+            schema,
+            table: item.name.clone(),
+            alias: self.unique_alias(&item.name),
+        };
+        self.tables.push(target.table.clone());
+
+        // Build column list for the subquery, recursing into nested embeds
+        let no_embeds = BTreeMap::new();
+        let nested = embed_params.map_or(&no_embeds, |params| &params.embedded);
+        let columns = self.build_embed_columns(cache, item, &target, nested)?;
+        let column_sql: Vec<&str> = columns.iter().map(|column| column.sql.as_str()).collect();
+
+        let subquery_alias = self.unique_alias(&format!("{}_1", target.alias));
+        let mut source = format!(
+            "{}.{} AS {}",
+            self.quote_identifier(&target.schema),
+            self.quote_identifier(&target.table),
+            self.quote_identifier(&target.alias)
+        );
+
+        let (join_condition, parent_columns) = match &rel.relation_type {
+            // orders.customer_id -> customers.id
+            RelationType::ManyToOne => (
+                fk.join_condition(&parent.alias, &target.alias),
+                &fk.from_columns,
+            ),
+            // customers.id <- orders.customer_id
+            RelationType::OneToMany => (
+                fk.join_condition(&target.alias, &parent.alias),
+                &fk.to_columns,
+            ),
+            // orders.id <- order_items.order_id, order_items.product_id -> products.id
+            RelationType::ManyToMany {
+                junction_table,
+                source_key,
+            } => {
+                let junction_alias = self.unique_alias(junction_table);
+                source.push_str(&format!(
+                    " INNER JOIN {}.{} AS {} ON {}",
+                    self.quote_identifier(&source_key.from_schema),
+                    self.quote_identifier(junction_table),
+                    self.quote_identifier(&junction_alias),
+                    fk.join_condition(&junction_alias, &target.alias)
+                ));
+                (
+                    source_key.join_condition(&junction_alias, &parent.alias),
+                    &source_key.to_columns,
+                )
+            }
+        };
 
-        // Build column list for the subquery
-        let column_list = if let Some(children) = &item.children {
-            children
+        // Correlate with the parent, then apply the embed's own filters and any
+        // existence checks of its nested embeds
+        let children = item.children.as_deref().unwrap_or(&[]);
+        let filters = embed_params.map_or(&[][..], |params| &params.filters[..]);
+        let mut conditions = vec![join_condition];
+        conditions.extend(self.build_level_conditions(
+            children,
+            &columns,
+            filters,
+            Some(&target.alias),
+        )?);
+        let where_sql = conditions.join(" AND ");
+        let modifiers = self.build_embed_modifiers(children, &columns, embed_params, &target)?;
+        let exists = Some(format!("SELECT 1 FROM {} WHERE {}", source, where_sql));
+        let source = format!("{}{}", source, lateral_joins(&columns));
+
+        if item.item_type == ItemType::Spread {
+            // The parent selects every column of a lateral subquery joined to its
+            // FROM; only plain columns are known by name, to be grouped by
+            let sub = self.quote_identifier(&subquery_alias);
+            let group_by = children
                 .iter()
-                .filter(|c| c.item_type == ItemType::Field)
-                .map(|c| {
-                    if c.name == "*" {
-                        "*".to_string()
-                    } else {
-                        self.quote_identifier(&c.name)
-                    }
+                .map(|child| match child.item_type {
+                    ItemType::Field if child.name != "*" => Some(format!(
+                        "{}.{}",
+                        sub,
+                        self.quote_identifier(child.output_name())
+                    )),
+                    _ => None,
                 })
-                .collect::<Vec<_>>()
-                .join(", ")
-        } else {
-            "*".to_string()
+                .collect::<Option<Vec<_>>>()
+                .filter(|_| item.children.is_some());
+            return Ok(SelectColumn {
+                sql: format!("{}.*", sub),
+                exists,
+                group_by,
+                lateral: Some(format!(
+                    " LEFT JOIN LATERAL (SELECT {} FROM {} WHERE {}{}) AS {} ON TRUE",
+                    column_sql.join(", "),
+                    source,
+                    where_sql,
+                    modifiers,
+                    sub
+                )),
+            });
+        }
+
+        let (aggregate, empty) = match rel.relation_type {
+            RelationType::ManyToOne => ("row_to_json", "null"),
+            RelationType::OneToMany | RelationType::ManyToMany { .. } => ("json_agg", "[]"),
         };
 
-        match rel.relation_type {
-            RelationType::ManyToOne => {
-                // orders.customer_id -> customers.id
-                // Generate: (SELECT row_to_json(c) FROM customers c WHERE c.id = orders.customer_id)
-                Ok(format!(
-                    "COALESCE((SELECT row_to_json({}_1) FROM (SELECT {} FROM {} {} WHERE {}) {}_1), 'null'::json) AS {}",
-                    rel_table,
-                    column_list,
-                    self.quote_identifier(rel_table),
-                    rel_table,
-                    rel.foreign_key.join_condition(current_table, rel_table),
-                    rel_table,
-                    self.quote_identifier(rel_table)
-                ))
-            }
-            RelationType::OneToMany => {
-                // customers.id <- orders.customer_id
-                // Generate: (SELECT COALESCE(json_agg(o), '[]') FROM orders o WHERE o.customer_id = customers.id)
-                Ok(format!(
-                    "COALESCE((SELECT json_agg({}_1) FROM (SELECT {} FROM {} {} WHERE {}) {}_1), '[]'::json) AS {}",
-                    rel_table,
-                    column_list,
-                    self.quote_identifier(rel_table),
-                    rel_table,
-                    rel.foreign_key.join_condition(rel_table, current_table),
-                    rel_table,
-                    self.quote_identifier(rel_table)
-                ))
+        Ok(SelectColumn {
+            sql: format!(
+                "COALESCE((SELECT {aggregate}({sub}) FROM (SELECT {columns} FROM {source} WHERE {where_sql}{modifiers}) {sub}), '{empty}'::json) AS {output}",
+                aggregate = aggregate,
+                sub = self.quote_identifier(&subquery_alias),
+                columns = column_sql.join(", "),
+                source = source,
+                where_sql = where_sql,
+                modifiers = modifiers,
+                empty = empty,
+                output = self.quote_identifier(item.output_name()),
+            ),
+            exists,
+            lateral: None,
+            group_by: Some(
+                parent_columns
+                    .iter()
+                    .map(|column| {
+                        format!(
+                            "{}.{}",
+                            self.quote_identifier(&parent.alias),
+                            self.quote_identifier(column)
+                        )
+                    })
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Builds the column list of an embedded subquery, qualifying columns with the
+    /// embed's alias and recursing into nested relations.
+    #[cfg(feature = "postgres")]
+    fn build_embed_columns(
+        &mut self,
+        cache: &crate::schema_cache::SchemaCache,
+        item: &SelectItem,
+        scope: &EmbedScope,
+        embedded: &BTreeMap<String, EmbedParams>,
+    ) -> Result<Vec<SelectColumn>, SqlError> {
+        let qualifier = self.quote_identifier(&scope.alias);
+
+        let Some(children) = &item.children else {
+            check_embedded_selected(&[], embedded)?;
+            return Ok(vec![SelectColumn::new(format!("{}.*", qualifier))]);
+        };
+        check_embedded_selected(children, embedded)?;
+
+        children
+            .iter()
+            .map(|child| match child.item_type {
+                ItemType::Field => Ok(SelectColumn::new(
+                    self.select_field_to_sql(Some(&scope.alias), child)?,
+                )),
+                ItemType::Relation | ItemType::Spread => {
+                    self.build_embed(cache, child, scope, find_embedded(embedded, child))
+                }
+            })
+            .collect()
+    }
+
+    /// Builds the GROUP BY of an embed's aggregates and the ORDER BY and
+    /// LIMIT/OFFSET its own parameters add to its subquery.
+    #[cfg(feature = "postgres")]
+    fn build_embed_modifiers(
+        &mut self,
+        children: &[SelectItem],
+        columns: &[SelectColumn],
+        embed_params: Option<&EmbedParams>,
+        scope: &EmbedScope,
+    ) -> Result<String, SqlError> {
+        let mut modifiers = self.group_by_sql(children, columns, Some(&scope.alias))?;
+        let Some(params) = embed_params else {
+            return Ok(modifiers);
+        };
+
+        if !params.order.is_empty() {
+            let order_sql =
+                self.order_by_sql(&params.order, children, Some(&scope.alias), Some(scope))?;
+            modifiers.push_str(&order_sql);
+        }
+        modifiers.push_str(&self.limit_offset_sql(params.limit, params.offset));
+
+        Ok(modifiers)
+    }
+
+    /// Builds the WHERE conditions of one select level.
+    ///
+    /// Filters naming an embedded relation with `is.null` become `NOT EXISTS`
+    /// (anti-join) and `not.is.null` becomes `EXISTS` (semi-join); `!inner`
+    /// embeds add an `EXISTS` check. Relations without an existence query (no
+    /// schema cache) are filtered like plain columns.
+    fn build_level_conditions(
+        &mut self,
+        items: &[SelectItem],
+        columns: &[SelectColumn],
+        filters: &[LogicCondition],
+        qualifier: Option<&str>,
+    ) -> Result<Vec<String>, SqlError> {
+        let mut conditions = Vec::new();
+
+        for condition in filters {
+            match embed_null_filter(items, columns, condition) {
+                Some((exists, true)) => conditions.push(format!("NOT EXISTS ({})", exists)),
+                Some((exists, false)) => conditions.push(format!("EXISTS ({})", exists)),
+                None => conditions.push(self.build_condition(condition, qualifier)?),
             }
-            RelationType::ManyToMany { junction_table } => {
-                // TODO: Implement M2M through junction tables
-                Err(SqlError::ManyToManyNotYetSupported {
-                    junction_table: junction_table.to_string(),
-                })
+        }
+
+        for (item, column) in items.iter().zip(columns) {
+            if let (JoinType::Inner, Some(exists)) = (item.join_type(), &column.exists) {
+                conditions.push(format!("EXISTS ({})", exists));
             }
         }
+
+        Ok(conditions)
+    }
+
+    /// Returns an alias for `base` that no other table in the query uses yet
+    #[cfg(feature = "postgres")]
+    fn unique_alias(&mut self, base: &str) -> String {
+        let mut alias = base.to_string();
+        let mut suffix = 1;
+        while self.aliases.contains(&alias) {
+            suffix += 1;
+            alias = format!("{}_{}", base, suffix);
+        }
+        self.aliases.push(alias.clone());
+        alias
     }
 
     fn build_relation_placeholder(&self, item: &SelectItem) -> Result<String, SqlError> {
-        let rel_alias = &item.name;
+        let rel_alias = &self.quote_identifier(&item.name);
+        let sub_alias = &self.quote_identifier(&format!("{}_sub", item.name));
 
         if let Some(children) = &item.children {
             let child_columns: Vec<String> = children
@@ -314,8 +985,9 @@ impl QueryBuilder {
                 ))
             } else {
                 Ok(format!(
-                    "(SELECT json_agg(row_to_json({rel_alias}_sub)) FROM (SELECT {columns} FROM {rel_alias}) {rel_alias}_sub) AS {rel_alias}",
+                    "(SELECT json_agg(row_to_json({sub_alias})) FROM (SELECT {columns} FROM {rel_alias}) {sub_alias}) AS {rel_alias}",
                     rel_alias = rel_alias,
+                    sub_alias = sub_alias,
                     columns = child_columns.join(", "),
                 ))
             }
@@ -355,19 +1027,36 @@ impl QueryBuilder {
     }
 
     pub(crate) fn build_filter(&mut self, condition: &LogicCondition) -> Result<String, SqlError> {
+        self.build_condition(condition, None)
+    }
+
+    /// Builds a filter condition, qualifying its columns with `qualifier` when given.
+    fn build_condition(
+        &mut self,
+        condition: &LogicCondition,
+        qualifier: Option<&str>,
+    ) -> Result<String, SqlError> {
         match condition {
-            LogicCondition::Filter(filter) => self.build_single_filter(filter),
-            LogicCondition::Logic(tree) => self.build_logic_tree(tree),
+            LogicCondition::Filter(filter) => self.build_single_filter(filter, qualifier),
+            LogicCondition::Logic(tree) => self.build_logic_tree(tree, qualifier),
         }
     }
 
-    fn build_single_filter(&mut self, filter: &Filter) -> Result<String, SqlError> {
-        let field_sql = self.field_to_sql(&filter.field);
+    fn build_single_filter(
+        &mut self,
+        filter: &Filter,
+        qualifier: Option<&str>,
+    ) -> Result<String, SqlError> {
+        let field_sql = self.qualified_field_to_sql(qualifier, &filter.field)?;
         let (clause, _) = self.operator_to_sql(&field_sql, filter)?;
         Ok(clause)
     }
 
-    fn build_logic_tree(&mut self, tree: &LogicTree) -> Result<String, SqlError> {
+    fn build_logic_tree(
+        &mut self,
+        tree: &LogicTree,
+        qualifier: Option<&str>,
+    ) -> Result<String, SqlError> {
         let joiner = if tree.operator == LogicOperator::And {
             " AND "
         } else {
@@ -377,7 +1066,7 @@ impl QueryBuilder {
         let conditions: Result<Vec<String>, SqlError> = tree
             .conditions
             .iter()
-            .map(|c| self.build_filter(c))
+            .map(|c| self.build_condition(c, qualifier))
             .collect();
 
         let conditions_sql = conditions?.join(joiner);
@@ -390,23 +1079,35 @@ impl QueryBuilder {
     }
 
     pub(crate) fn build_order_clause(&mut self, order_terms: &[OrderTerm]) -> Result<(), SqlError> {
-        let clauses: Result<Vec<String>, SqlError> = order_terms
-            .iter()
-            .map(|term| self.order_term_to_sql(term))
-            .collect();
+        let order_sql = self.order_by_sql(order_terms, &[], None, None)?;
+        self.sql.push_str(&order_sql);
+        Ok(())
+    }
 
-        match clauses {
-            Ok(clauses) => {
-                self.sql.push_str(" ORDER BY ");
-                self.sql.push_str(&clauses.join(", "));
-                Ok(())
-            }
-            Err(e) => Err(e),
+    /// Builds an ORDER BY clause, qualifying columns with `qualifier` when given.
+    ///
+    /// Terms naming an embedded resource are resolved against `parent`, the table
+    /// being ordered, using the hint of the matching embed in `items` if any.
+    fn order_by_sql(
+        &mut self,
+        order_terms: &[OrderTerm],
+        items: &[SelectItem],
+        qualifier: Option<&str>,
+        parent: Option<&EmbedScope>,
+    ) -> Result<String, SqlError> {
+        let mut clauses = Vec::with_capacity(order_terms.len());
+        for term in order_terms {
+            let field_sql = match &term.relation {
+                Some(relation) => self.related_order_sql(relation, &term.field, items, parent)?,
+                None => self.qualified_field_to_sql(qualifier, &term.field)?,
+            };
+            clauses.push(self.order_term_to_sql(term, &field_sql));
         }
+
+        Ok(format!(" ORDER BY {}", clauses.join(", ")))
     }
 
-    fn order_term_to_sql(&self, term: &OrderTerm) -> Result<String, SqlError> {
-        let field_sql = self.field_to_sql(&term.field);
+    fn order_term_to_sql(&self, term: &OrderTerm, field_sql: &str) -> String {
         let dir_sql = if term.direction == Direction::Desc {
             " DESC"
         } else {
@@ -419,7 +1120,65 @@ impl QueryBuilder {
             None => "",
         };
 
-        Ok(format!("{}{}{}", field_sql, dir_sql, nulls_sql))
+        format!("{}{}{}", field_sql, dir_sql, nulls_sql)
+    }
+
+    /// Builds the value of a to-one related column used for ordering
+    #[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+    fn related_order_sql(
+        &mut self,
+        relation: &str,
+        field: &Field,
+        items: &[SelectItem],
+        parent: Option<&EmbedScope>,
+    ) -> Result<String, SqlError> {
+        #[cfg(feature = "postgres")]
+        if let (Some(cache), Some(parent)) = (self.schema_cache.clone(), parent) {
+            return self.build_related_order(&cache, relation, field, items, parent);
+        }
+
+        Err(SqlError::RelatedOrderUnavailable(relation.to_string()))
+    }
+
+    /// Resolves `relation` from `parent` and selects `field` from its single
+    /// matching row with a correlated subquery.
+    #[cfg(feature = "postgres")]
+    fn build_related_order(
+        &mut self,
+        cache: &crate::schema_cache::SchemaCache,
+        relation: &str,
+        field: &Field,
+        items: &[SelectItem],
+        parent: &EmbedScope,
+    ) -> Result<String, SqlError> {
+        use crate::schema_cache::RelationType;
+
+        let embed = items.iter().find(|item| {
+            item.item_type != ItemType::Field
+                && (item.alias.as_deref() == Some(relation) || item.name == relation)
+        });
+        let (table, hint) = match embed {
+            Some(item) => (item.name.as_str(), item.relation_hint()),
+            None => (relation, None),
+        };
+
+        let rel = cache.resolve_relationship(&parent.schema, &parent.table, table, hint)?;
+        if rel.relation_type != RelationType::ManyToOne {
+            return Err(SqlError::OrderByToMany(relation.to_string()));
+        }
+
+        let fk = &rel.foreign_key;
+        let alias = self.unique_alias(table);
+        self.tables.push(table.to_string());
+
+        Ok(format!(
+            "(SELECT {} FROM {}.{} AS {} WHERE {})",
+            self.qualified_field_to_sql(Some(&alias), field)?,
+            self.quote_identifier(&fk.to_schema),
+            self.quote_identifier(table),
+            self.quote_identifier(&alias),
+            fk.join_condition(&parent.alias, &alias)
+        ))
     }
 
     pub(crate) fn build_limit_offset(
@@ -427,25 +1186,28 @@ impl QueryBuilder {
         limit: Option<u64>,
         offset: Option<u64>,
     ) -> Result<(), SqlError> {
+        let limit_sql = self.limit_offset_sql(limit, offset);
+        self.sql.push_str(&limit_sql);
+        Ok(())
+    }
+
+    fn limit_offset_sql(&mut self, limit: Option<u64>, offset: Option<u64>) -> String {
         match (limit, offset) {
             (Some(lim), Some(off)) => {
                 let lim_ref = self.add_param(serde_json::Value::Number(lim.into()));
                 let off_ref = self.add_param(serde_json::Value::Number(off.into()));
-                self.sql
-                    .push_str(&format!(" LIMIT {} OFFSET {}", lim_ref, off_ref));
+                format!(" LIMIT {} OFFSET {}", lim_ref, off_ref)
             }
             (Some(lim), None) => {
                 let lim_ref = self.add_param(serde_json::Value::Number(lim.into()));
-                self.sql.push_str(&format!(" LIMIT {}", lim_ref));
+                format!(" LIMIT {}", lim_ref)
             }
             (None, Some(off)) => {
                 let off_ref = self.add_param(serde_json::Value::Number(off.into()));
-                self.sql.push_str(&format!(" OFFSET {}", off_ref));
+                format!(" OFFSET {}", off_ref)
             }
-            (None, None) => {}
+            (None, None) => String::new(),
         }
-
-        Ok(())
     }
 
     fn operator_to_sql(
@@ -565,7 +1327,7 @@ impl QueryBuilder {
                 FilterValue::Single(_),
             ) => {
                 let param_ref = self.add_param(filter.value.to_json());
-                let lang = filter.language.as_deref().unwrap_or("english");
+                let lang = Literal(filter.language.as_deref().unwrap_or("english"));
                 let ts_fn = match filter.operator {
                     FilterOperator::Fts | FilterOperator::Plfts => "plainto_tsquery",
                     FilterOperator::Phfts => "phraseto_tsquery",
@@ -575,7 +1337,7 @@ impl QueryBuilder {
                 let not_prefix = if filter.negated { "NOT " } else { "" };
                 (
                     format!(
-                        "{}to_tsvector('{}', {}) @@ {}('{}', {})",
+                        "{}to_tsvector({}, {}) @@ {}({}, {})",
                         not_prefix, lang, field, ts_fn, lang, param_ref
                     ),
                     1,
@@ -657,18 +1419,31 @@ impl QueryBuilder {
         }
     }
 
-    fn field_to_sql(&self, field: &Field) -> String {
-        let base = self.quote_identifier(&field.name);
+    fn qualified_field_to_sql(
+        &self,
+        qualifier: Option<&str>,
+        field: &Field,
+    ) -> Result<String, SqlError> {
+        let base = match qualifier {
+            Some(qualifier) => format!(
+                "{}.{}",
+                self.quote_identifier(qualifier),
+                self.quote_identifier(&field.name)
+            ),
+            None => self.quote_identifier(&field.name),
+        };
+
+        let cast = field.cast.as_deref().map(TypeName::parse).transpose()?;
 
-        match (&field.json_path[..], &field.cast) {
+        Ok(match (&field.json_path[..], cast) {
             ([], None) => base,
             ([], Some(cast)) => format!("{}::{}", base, cast),
             (json_path, cast_opt) => {
                 let json_path_sql: Vec<String> = json_path
                     .iter()
                     .map(|op| match op {
-                        JsonOp::Arrow(key) => format!("->'{}'", key),
-                        JsonOp::DoubleArrow(key) => format!("->>'{}'", key),
+                        JsonOp::Arrow(key) => format!("->{}", Literal(key)),
+                        JsonOp::DoubleArrow(key) => format!("->>{}", Literal(key)),
                         JsonOp::ArrayIndex(idx) => format!("->{}", idx),
                     })
                     .collect();
@@ -681,7 +1456,7 @@ impl QueryBuilder {
                     format!("{}{}", base, json_path_str)
                 }
             }
-        }
+        })
     }
 
     pub(crate) fn add_param(&mut self, value: serde_json::Value) -> String {
@@ -692,8 +1467,78 @@ impl QueryBuilder {
     }
 
     fn quote_identifier(&self, name: &str) -> String {
-        let escaped = name.replace('"', "\"\"");
-        format!("\"{}\"", escaped)
+        Ident(name).to_string()
+    }
+}
+
+/// Looks up the embed parameters for a relation, by alias first and then by name.
+fn find_embedded<'a>(
+    embedded: &'a BTreeMap<String, EmbedParams>,
+    item: &SelectItem,
+) -> Option<&'a EmbedParams> {
+    item.alias
+        .as_ref()
+        .and_then(|alias| embedded.get(alias))
+        .or_else(|| embedded.get(&item.name))
+}
+
+/// Matches `rel=is.null` / `rel=not.is.null` filters on an embedded relation,
+/// returning its existence query and whether the relation must be absent.
+fn embed_null_filter<'a>(
+    items: &[SelectItem],
+    columns: &'a [SelectColumn],
+    condition: &LogicCondition,
+) -> Option<(&'a str, bool)> {
+    let LogicCondition::Filter(filter) = condition else {
+        return None;
+    };
+    if filter.operator != FilterOperator::Is
+        || filter.quantifier.is_some()
+        || !filter.field.json_path.is_empty()
+        || filter.field.cast.is_some()
+    {
+        return None;
+    }
+
+    let is_null = match filter.value.as_str()?.to_lowercase().as_str() {
+        "null" => !filter.negated,
+        "not_null" => filter.negated,
+        _ => return None,
+    };
+
+    items
+        .iter()
+        .zip(columns)
+        .find(|(item, _)| {
+            item.item_type != ItemType::Field && item.output_name() == filter.field.name
+        })
+        .and_then(|(_, column)| column.exists.as_deref())
+        .map(|exists| (exists, is_null))
+}
+
+/// Returns true when a spread selects nothing but aggregates, as in `...orders(total.sum())`
+#[cfg(feature = "postgres")]
+fn is_aggregate_spread(item: &SelectItem) -> bool {
+    item.children.as_deref().is_some_and(|children| {
+        !children.is_empty() && children.iter().all(SelectItem::is_aggregate)
+    })
+}
+
+/// Rejects embed parameters that do not name a relation selected alongside them.
+fn check_embedded_selected(
+    items: &[SelectItem],
+    embedded: &BTreeMap<String, EmbedParams>,
+) -> Result<(), SqlError> {
+    let unknown = embedded.keys().find(|key| {
+        !items.iter().any(|item| {
+            item.item_type != ItemType::Field
+                && (item.alias.as_ref() == Some(*key) || &item.name == *key)
+        })
+    });
+
+    match unknown {
+        Some(key) => Err(SqlError::EmbedNotSelected(key.clone())),
+        None => Ok(()),
     }
 }
 
@@ -729,14 +1574,17 @@ mod tests {
     fn test_field_to_sql_simple() {
         let builder = QueryBuilder::new();
         let field = Field::new("id");
-        assert_eq!(builder.field_to_sql(&field), "\"id\"");
+        assert_eq!(
+            builder.qualified_field_to_sql(None, &field).unwrap(),
+            "\"id\""
+        );
     }
 
     #[test]
     fn test_field_to_sql_with_json_path() {
         let builder = QueryBuilder::new();
         let field = Field::new("data").with_json_path(vec![JsonOp::Arrow("key".to_string())]);
-        let sql = builder.field_to_sql(&field);
+        let sql = builder.qualified_field_to_sql(None, &field).unwrap();
         assert!(sql.contains("\"data\"->'key'"));
     }
 
@@ -744,7 +1592,10 @@ mod tests {
     fn test_field_to_sql_with_cast() {
         let builder = QueryBuilder::new();
         let field = Field::new("price").with_cast("numeric");
-        assert_eq!(builder.field_to_sql(&field), "\"price\"::numeric");
+        assert_eq!(
+            builder.qualified_field_to_sql(None, &field).unwrap(),
+            "\"price\"::numeric"
+        );
     }
 
     #[test]
@@ -1067,4 +1918,736 @@ mod tests {
         let (sql, _) = builder.operator_to_sql("\"status\"", &filter).unwrap();
         assert_eq!(sql, "\"status\" = ALL($1)");
     }
+
+    #[test]
+    fn test_select_json_paths_and_casts() {
+        let params = crate::parse_query_string(
+            "select=id,data->address->>city,price::text,total:amount::numeric,data->>qty::int",
+        )
+        .unwrap();
+        let result = QueryBuilder::new().build_select("items", &params).unwrap();
+        assert_eq!(
+            result.query,
+            r#"SELECT "id", "data"->'address'->>'city' AS "city", "price"::text AS "price", "amount"::numeric AS "total", ("data"->>'qty')::int AS "qty" FROM "items""#
+        );
+    }
+
+    #[test]
+    fn test_select_aggregates_group_by_plain_columns() {
+        let params = crate::parse_query_string(
+            "select=category,total:price.sum()::int,count()&stock=gt.0&order=category.asc",
+        )
+        .unwrap();
+        let result = QueryBuilder::new()
+            .build_select("products", &params)
+            .unwrap();
+        assert_eq!(
+            result.query,
+            r#"SELECT "category", sum("price")::int AS "total", count(*) AS "count" FROM "products" WHERE "stock" > $1 GROUP BY "category" ORDER BY "category" ASC"#
+        );
+    }
+
+    #[test]
+    fn test_select_aggregates_without_plain_columns() {
+        let params = crate::parse_query_string("select=price.avg(),data->>qty::int.max()").unwrap();
+        let result = QueryBuilder::new()
+            .build_select("products", &params)
+            .unwrap();
+        assert_eq!(
+            result.query,
+            r#"SELECT avg("price") AS "avg", max(("data"->>'qty')::int) AS "max" FROM "products""#
+        );
+    }
+
+    #[test]
+    fn test_user_input_is_escaped_in_sql_text() {
+        let filter = Filter::new(
+            Field::new("da\"ta").with_json_path(vec![JsonOp::DoubleArrow("it's".to_string())]),
+            FilterOperator::Fts,
+            FilterValue::Single("cat".to_string()),
+        )
+        .with_language("english', 'x");
+        let params = ParsedParams::new().with_filters(vec![LogicCondition::Filter(filter)]);
+        let result = QueryBuilder::new().build_select("items", &params).unwrap();
+        assert_eq!(
+            result.query,
+            r#"SELECT * FROM "items" WHERE to_tsvector('english'', ''x', "da""ta"->>'it''s') @@ plainto_tsquery('english'', ''x', $1)"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_casts_are_rejected() {
+        for query in ["select=id::text--x", "select=total:price.sum()::int;drop"] {
+            let params = crate::parse_query_string(query).unwrap();
+            let err = QueryBuilder::new()
+                .build_select("items", &params)
+                .unwrap_err();
+            assert_eq!(err, SqlError::InvalidTypeCastForSql, "{query}");
+        }
+    }
+
+    #[test]
+    fn test_count_queries_ignore_order_and_pagination() {
+        let params =
+            crate::parse_query_string("age=gte.18&order=name.asc&limit=10&offset=20").unwrap();
+
+        let result = QueryBuilder::new()
+            .with_count(Count::Exact)
+            .build_select("users", &params)
+            .unwrap();
+        let count = result.count.unwrap();
+        assert_eq!(
+            count.exact.as_deref(),
+            Some(
+                r#"SELECT pg_catalog.count(*) FROM (SELECT * FROM "users" WHERE "age" >= $1) AS "pgrst_source""#
+            )
+        );
+        assert_eq!(count.planned, None);
+        assert_eq!(count.params, vec![serde_json::json!("18")]);
+        assert_eq!(result.params.len(), 3);
+
+        let count = QueryBuilder::new()
+            .with_count(Count::Planned)
+            .build_select("users", &params)
+            .unwrap()
+            .count
+            .unwrap();
+        assert_eq!(count.exact, None);
+        assert_eq!(
+            count.planned.as_deref(),
+            Some(r#"EXPLAIN (FORMAT JSON) SELECT * FROM "users" WHERE "age" >= $1"#)
+        );
+    }
+
+    #[test]
+    fn test_estimated_count_switches_on_threshold() {
+        let params = crate::parse_query_string("select=category,count()").unwrap();
+        let count = QueryBuilder::new()
+            .with_count(Count::Estimated)
+            .with_estimated_count_threshold(500)
+            .build_select("products", &params)
+            .unwrap()
+            .count
+            .unwrap();
+
+        // Grouped queries count their groups
+        assert!(count
+            .exact
+            .as_deref()
+            .unwrap()
+            .ends_with(r#"GROUP BY "category") AS "pgrst_source""#));
+        assert!(count.planned.is_some());
+        assert_eq!(count.threshold, Some(500));
+        assert!(count.needs_exact(500));
+        assert!(!count.needs_exact(501));
+    }
+
+    #[test]
+    fn test_no_count_query_by_default() {
+        let params = crate::parse_query_string("limit=5").unwrap();
+        let result = QueryBuilder::new().build_select("users", &params).unwrap();
+        assert!(result.count.is_none());
+        assert!(!serde_json::to_string(&result).unwrap().contains("count"));
+    }
+
+    #[test]
+    fn test_wrapped_response_with_exact_total() {
+        let params =
+            crate::parse_query_string("select=id&age=gte.18&order=id.asc&limit=10").unwrap();
+        let result = QueryBuilder::new()
+            .with_count(Count::Exact)
+            .with_wrapped_response(Plurality::Multiple)
+            .build_select("users", &params)
+            .unwrap();
+
+        assert_eq!(
+            result.query,
+            concat!(
+                r#"WITH "pgrst_source" AS (SELECT "id" FROM "users" WHERE "age" >= $1 ORDER BY "id" ASC LIMIT $2) "#,
+                r#"SELECT (SELECT pg_catalog.count(*) FROM (SELECT "id" FROM "users" WHERE "age" >= $1) AS "pgrst_source_count") AS "total_result_set", "#,
+                r#"pg_catalog.count("_postgrest_t") AS "page_total", "#,
+                r#"coalesce(json_agg("_postgrest_t"), '[]') AS "body" "#,
+                r#"FROM (SELECT * FROM "pgrst_source") AS "_postgrest_t""#
+            )
+        );
+        assert_eq!(result.params.len(), 2);
+        assert_eq!(result.wrapped, Some(Plurality::Multiple));
+        // Only singular responses constrain the row count
+        assert!(result.check_page_total(0).is_ok());
+    }
+
+    #[test]
+    fn test_wrapped_singular_response() {
+        let params = crate::parse_query_string("id=eq.1").unwrap();
+        let result = QueryBuilder::new()
+            .with_count(Count::Planned)
+            .with_wrapped_response(Plurality::Singular)
+            .build_select("users", &params)
+            .unwrap();
+
+        assert!(result
+            .query
+            .contains(r#"SELECT NULL::bigint AS "total_result_set""#));
+        assert!(result
+            .query
+            .contains(r#"coalesce(json_agg("_postgrest_t")->0, 'null') AS "body""#));
+        assert_eq!(
+            result.check_page_total(0),
+            Err(SqlError::SingularRowCount(0))
+        );
+        assert!(result.check_page_total(1).is_ok());
+        // The companion count queries still select the bare rows
+        assert_eq!(
+            result.count.unwrap().planned.as_deref(),
+            Some(r#"EXPLAIN (FORMAT JSON) SELECT * FROM "users" WHERE "id" = $1"#)
+        );
+    }
+
+    #[test]
+    fn test_planned_rows_from_explain_output() {
+        let explain = serde_json::json!([{"Plan": {"Plan Rows": 1250}}]);
+        assert_eq!(CountQuery::planned_rows(&explain), Some(1250));
+        assert_eq!(CountQuery::planned_rows(&serde_json::json!([])), None);
+    }
+
+    #[test]
+    fn test_related_order_without_schema_cache() {
+        let params = crate::parse_query_string("order=clients(name).desc").unwrap();
+        let err = QueryBuilder::new()
+            .build_select("projects", &params)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SqlError::RelatedOrderUnavailable("clients".to_string())
+        );
+    }
+
+    #[test]
+    fn test_relation_null_filter_without_schema_cache() {
+        let params = crate::parse_query_string("select=id,client(id)&client=is.null").unwrap();
+        let result = QueryBuilder::new().build_select("orders", &params).unwrap();
+        assert!(result.query.ends_with(r#"WHERE "client" IS NULL"#));
+    }
+
+    #[cfg(feature = "postgres")]
+    mod embedding {
+        use super::*;
+        use crate::parse_query_string;
+        use crate::schema_cache::{ForeignKey, SchemaCache};
+        use std::sync::Arc;
+
+        fn fk(from_table: &str, from_column: &str, to_table: &str) -> ForeignKey {
+            ForeignKey {
+                from_schema: "public".to_string(),
+                from_table: from_table.to_string(),
+                from_columns: vec![from_column.to_string()],
+                to_schema: "public".to_string(),
+                to_table: to_table.to_string(),
+                to_columns: vec!["id".to_string()],
+                constraint_name: format!("{}_{}_fkey", from_table, from_column),
+            }
+        }
+
+        fn shop_cache() -> Arc<SchemaCache> {
+            let mut cache = SchemaCache::new();
+            cache.add_foreign_key(fk("orders", "customer_id", "customers"));
+            cache.add_foreign_key(fk("order_items", "order_id", "orders"));
+            cache.add_foreign_key(fk("order_items", "product_id", "products"));
+            cache.add_primary_key("public", "order_items", vec!["id".to_string()]);
+            cache.add_unique_key(
+                "public",
+                "order_items",
+                vec!["order_id".to_string(), "product_id".to_string()],
+            );
+            Arc::new(cache)
+        }
+
+        fn build(table: &str, query: &str) -> Result<QueryResult, SqlError> {
+            build_with(shop_cache(), table, query)
+        }
+
+        fn build_with(
+            cache: Arc<SchemaCache>,
+            table: &str,
+            query: &str,
+        ) -> Result<QueryResult, SqlError> {
+            let params = parse_query_string(query).unwrap();
+            QueryBuilder::new()
+                .with_schema_cache(cache)
+                .build_select(table, &params)
+        }
+
+        #[test]
+        fn test_embed_many_to_one() {
+            let result = build("orders", "select=id,customers(name)").unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "id", COALESCE((SELECT row_to_json("customers_1") FROM (SELECT "customers"."name" FROM "public"."customers" AS "customers" WHERE "orders"."customer_id" = "customers"."id") "customers_1"), 'null'::json) AS "customers" FROM "orders""#
+            );
+        }
+
+        #[test]
+        fn test_embed_nested_to_any_depth() {
+            let result = build(
+                "customers",
+                "select=id,orders(id,order_items(quantity,products(name)))",
+            )
+            .unwrap();
+
+            assert!(result
+                .query
+                .contains(r#""orders"."customer_id" = "customers"."id""#));
+            assert!(result
+                .query
+                .contains(r#""order_items"."order_id" = "orders"."id""#));
+            assert!(result
+                .query
+                .contains(r#""order_items"."product_id" = "products"."id""#));
+            assert!(result.query.contains(r#"SELECT "products"."name" FROM"#));
+            assert_eq!(
+                result.tables,
+                vec!["customers", "orders", "order_items", "products"]
+            );
+        }
+
+        #[test]
+        fn test_embed_same_table_twice_gets_unique_aliases() {
+            let result = build("customers", "select=id,orders(id,customers(name))").unwrap();
+
+            assert!(result
+                .query
+                .contains(r#"FROM "public"."customers" AS "customers_2""#));
+            assert!(result
+                .query
+                .contains(r#""orders"."customer_id" = "customers_2"."id""#));
+            assert!(result
+                .query
+                .contains(r#""orders"."customer_id" = "customers"."id""#));
+        }
+
+        #[test]
+        fn test_embed_uses_relation_alias_as_output_name() {
+            let result = build("orders", "select=id,buyer:customers(*)").unwrap();
+            assert!(result.query.contains(r#"SELECT "customers".* FROM"#));
+            assert!(result
+                .query
+                .ends_with(r#"'null'::json) AS "buyer" FROM "orders""#));
+        }
+
+        #[test]
+        fn test_embed_many_to_many_through_junction() {
+            let result = build("orders", "select=*,products(*)").unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT *, COALESCE((SELECT json_agg("products_1") FROM (SELECT "products".* FROM "public"."products" AS "products" INNER JOIN "public"."order_items" AS "order_items" ON "order_items"."product_id" = "products"."id" WHERE "order_items"."order_id" = "orders"."id") "products_1"), '[]'::json) AS "products" FROM "orders""#
+            );
+        }
+
+        #[test]
+        fn test_embed_many_to_many_ambiguous_junctions() {
+            let mut cache = (*shop_cache()).clone();
+            cache.add_foreign_key(fk("wishlist_items", "order_id", "orders"));
+            cache.add_foreign_key(fk("wishlist_items", "product_id", "products"));
+            cache.add_primary_key(
+                "public",
+                "wishlist_items",
+                vec!["order_id".to_string(), "product_id".to_string()],
+            );
+
+            let err = build_with(Arc::new(cache), "orders", "select=products(*)").unwrap_err();
+            assert_eq!(
+                err,
+                SqlError::AmbiguousRelationship {
+                    from_table: "orders".to_string(),
+                    to_table: "products".to_string(),
+                    candidates: vec![
+                        "products!order_items".to_string(),
+                        "products!wishlist_items".to_string()
+                    ],
+                }
+            );
+        }
+
+        #[test]
+        fn test_embed_composite_foreign_key() {
+            let mut cache = SchemaCache::new();
+            cache.add_foreign_key(ForeignKey {
+                from_schema: "public".to_string(),
+                from_table: "tenant_tasks".to_string(),
+                from_columns: vec!["tenant_id".to_string(), "project_id".to_string()],
+                to_schema: "public".to_string(),
+                to_table: "tenant_projects".to_string(),
+                to_columns: vec!["tenant_id".to_string(), "id".to_string()],
+                constraint_name: "tenant_tasks_tenant_id_project_id_fkey".to_string(),
+            });
+            let cache = Arc::new(cache);
+
+            let result = build_with(
+                cache.clone(),
+                "tenant_tasks",
+                "select=title,tenant_projects(name)",
+            )
+            .unwrap();
+            assert!(result.query.contains(
+                r#"WHERE "tenant_tasks"."tenant_id" = "tenant_projects"."tenant_id" AND "tenant_tasks"."project_id" = "tenant_projects"."id""#
+            ));
+
+            let result =
+                build_with(cache, "tenant_projects", "select=name,tenant_tasks(title)").unwrap();
+            assert!(result.query.contains(
+                r#"WHERE "tenant_tasks"."tenant_id" = "tenant_projects"."tenant_id" AND "tenant_tasks"."project_id" = "tenant_projects"."id""#
+            ));
+        }
+
+        #[test]
+        fn test_embed_aggregates_group_inside_subquery() {
+            let result = build(
+                "customers",
+                "select=name,orders(status,count(),total_amount.sum())&orders.order=status",
+            )
+            .unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "name", COALESCE((SELECT json_agg("orders_1") FROM (SELECT "orders"."status", count(*) AS "count", sum("orders"."total_amount") AS "sum" FROM "public"."orders" AS "orders" WHERE "orders"."customer_id" = "customers"."id" GROUP BY "orders"."status" ORDER BY "orders"."status" ASC) "orders_1"), '[]'::json) AS "orders" FROM "customers""#
+            );
+        }
+
+        #[test]
+        fn test_order_by_to_one_related_column() {
+            let result = build(
+                "orders",
+                "select=id,customers(name)&order=customers(name).desc.nullslast,id",
+            )
+            .unwrap();
+            assert!(result.query.ends_with(
+                r#" FROM "orders" ORDER BY (SELECT "customers_2"."name" FROM "public"."customers" AS "customers_2" WHERE "orders"."customer_id" = "customers_2"."id") DESC NULLS LAST, "id" ASC"#
+            ));
+
+            // The relation does not have to be selected
+            let result = build("orders", "select=id&order=customers(name)").unwrap();
+            assert!(result.query.ends_with(
+                r#"ORDER BY (SELECT "customers"."name" FROM "public"."customers" AS "customers" WHERE "orders"."customer_id" = "customers"."id") ASC"#
+            ));
+        }
+
+        #[test]
+        fn test_order_by_related_column_inside_embed() {
+            let result = build(
+                "orders",
+                "select=id,order_items(quantity)&order_items.order=products(name).desc",
+            )
+            .unwrap();
+            assert!(result.query.contains(
+                r#"WHERE "order_items"."order_id" = "orders"."id" ORDER BY (SELECT "products"."name" FROM "public"."products" AS "products" WHERE "order_items"."product_id" = "products"."id") DESC)"#
+            ));
+        }
+
+        #[test]
+        fn test_order_by_related_column_uses_embed_alias_and_hint() {
+            let mut cache = SchemaCache::new();
+            cache.add_foreign_key(fk("orders", "billing_address_id", "addresses"));
+            cache.add_foreign_key(fk("orders", "shipping_address_id", "addresses"));
+            let cache = Arc::new(cache);
+
+            let result = build_with(
+                cache.clone(),
+                "orders",
+                "select=id,shipping:addresses!shipping_address_id(city)&order=shipping(city)",
+            )
+            .unwrap();
+            assert!(result.query.contains(
+                r#"ORDER BY (SELECT "addresses_2"."city" FROM "public"."addresses" AS "addresses_2" WHERE "orders"."shipping_address_id" = "addresses_2"."id") ASC"#
+            ));
+
+            let err = build_with(cache, "orders", "order=addresses(city)").unwrap_err();
+            assert!(matches!(err, SqlError::AmbiguousRelationship { .. }));
+        }
+
+        #[test]
+        fn test_order_by_to_many_related_column_is_rejected() {
+            let err = build("customers", "order=orders(total_amount)").unwrap_err();
+            assert_eq!(err, SqlError::OrderByToMany("orders".to_string()));
+
+            let err = build("orders", "order=products(name)").unwrap_err();
+            assert_eq!(err, SqlError::OrderByToMany("products".to_string()));
+        }
+
+        #[test]
+        fn test_embed_filters_order_and_limit() {
+            let result = build(
+                "customers",
+                "select=id,orders(id,status)&status=eq.active&orders.status=eq.completed&orders.order=id.desc&orders.limit=2&orders.offset=1",
+            )
+            .unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "id", COALESCE((SELECT json_agg("orders_1") FROM (SELECT "orders"."id", "orders"."status" FROM "public"."orders" AS "orders" WHERE "orders"."customer_id" = "customers"."id" AND "orders"."status" = $1 ORDER BY "orders"."id" DESC LIMIT $2 OFFSET $3) "orders_1"), '[]'::json) AS "orders" FROM "customers" WHERE "status" = $4"#
+            );
+            assert_eq!(
+                result.params,
+                vec![
+                    serde_json::json!("completed"),
+                    serde_json::json!(2),
+                    serde_json::json!(1),
+                    serde_json::json!("active"),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_embed_nested_params_use_nested_alias() {
+            let result = build(
+                "customers",
+                "select=id,orders(id,customers(name))&orders.customers.or=(name.eq.Ann,name.eq.Bob)",
+            )
+            .unwrap();
+            assert!(result.query.contains(
+                r#""orders"."customer_id" = "customers_2"."id" AND ("customers_2"."name" = $1 OR "customers_2"."name" = $2)"#
+            ));
+        }
+
+        #[test]
+        fn test_embed_params_match_relation_alias() {
+            let result = build(
+                "orders",
+                "select=id,buyer:customers(name)&buyer.name=like.A*",
+            )
+            .unwrap();
+            assert!(result.query.contains(
+                r#""orders"."customer_id" = "customers"."id" AND "customers"."name" LIKE $1"#
+            ));
+        }
+
+        #[test]
+        fn test_embed_params_for_unselected_relation() {
+            let err = build("customers", "select=id,orders(id)&products.limit=1").unwrap_err();
+            assert_eq!(err, SqlError::EmbedNotSelected("products".to_string()));
+
+            let err = build("customers", "orders.limit=1").unwrap_err();
+            assert_eq!(err, SqlError::EmbedNotSelected("orders".to_string()));
+
+            let err = build("customers", "select=orders(id)&orders.items.limit=1").unwrap_err();
+            assert_eq!(err, SqlError::EmbedNotSelected("items".to_string()));
+        }
+
+        #[test]
+        fn test_embed_inner_join_filters_parent_rows() {
+            let result = build(
+                "customers",
+                "select=id,orders!inner(id)&orders.status=eq.completed",
+            )
+            .unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "id", COALESCE((SELECT json_agg("orders_1") FROM (SELECT "orders"."id" FROM "public"."orders" AS "orders" WHERE "orders"."customer_id" = "customers"."id" AND "orders"."status" = $1) "orders_1"), '[]'::json) AS "orders" FROM "customers" WHERE EXISTS (SELECT 1 FROM "public"."orders" AS "orders" WHERE "orders"."customer_id" = "customers"."id" AND "orders"."status" = $1)"#
+            );
+            assert_eq!(result.params, vec![serde_json::json!("completed")]);
+        }
+
+        #[test]
+        fn test_embed_left_join_is_default() {
+            let inner = build("orders", "select=id,customers!left(name)").unwrap();
+            let plain = build("orders", "select=id,customers(name)").unwrap();
+            assert_eq!(inner.query, plain.query);
+        }
+
+        #[test]
+        fn test_embed_nested_inner_join() {
+            let result = build(
+                "customers",
+                "select=id,orders!inner(id,products!inner(name))",
+            )
+            .unwrap();
+            let products_exists = r#"EXISTS (SELECT 1 FROM "public"."products" AS "products" INNER JOIN "public"."order_items" AS "order_items" ON "order_items"."product_id" = "products"."id" WHERE "order_items"."order_id" = "orders"."id")"#;
+
+            // The orders embed keeps only orders with products, and so does the root check
+            assert!(result.query.contains(&format!(
+                r#"WHERE "orders"."customer_id" = "customers"."id" AND {}) "orders_1""#,
+                products_exists
+            )));
+            assert!(result.query.ends_with(&format!(
+                r#"FROM "customers" WHERE EXISTS (SELECT 1 FROM "public"."orders" AS "orders" WHERE "orders"."customer_id" = "customers"."id" AND {})"#,
+                products_exists
+            )));
+        }
+
+        #[test]
+        fn test_embed_is_null_anti_join() {
+            let result = build(
+                "customers",
+                "select=id,orders(id)&orders=is.null&name=not.is.null",
+            )
+            .unwrap();
+            assert!(result.query.ends_with(
+                r#"FROM "customers" WHERE NOT EXISTS (SELECT 1 FROM "public"."orders" AS "orders" WHERE "orders"."customer_id" = "customers"."id") AND "name" IS NOT NULL"#
+            ));
+        }
+
+        #[test]
+        fn test_embed_not_is_null_semi_join() {
+            let result = build(
+                "orders",
+                "select=id,buyer:customers(name)&buyer=not.is.null&buyer.name=eq.Ann",
+            )
+            .unwrap();
+            assert!(result.query.ends_with(
+                r#"FROM "orders" WHERE EXISTS (SELECT 1 FROM "public"."customers" AS "customers" WHERE "orders"."customer_id" = "customers"."id" AND "customers"."name" = $1)"#
+            ));
+        }
+
+        #[test]
+        fn test_embed_is_null_inside_embed() {
+            let result = build(
+                "customers",
+                "select=id,orders(id,order_items(id))&orders.order_items=is.null",
+            )
+            .unwrap();
+            assert!(result.query.contains(
+                r#"WHERE "orders"."customer_id" = "customers"."id" AND NOT EXISTS (SELECT 1 FROM "public"."order_items" AS "order_items" WHERE "order_items"."order_id" = "orders"."id")) "orders_1""#
+            ));
+        }
+
+        #[test]
+        fn test_embed_disambiguated_by_hints() {
+            let mut cache = (*shop_cache()).clone();
+            cache.add_foreign_key(fk("orders", "billing_address_id", "addresses"));
+            cache.add_foreign_key(fk("orders", "shipping_address_id", "addresses"));
+            let cache = Arc::new(cache);
+
+            let err = build_with(cache.clone(), "orders", "select=id,addresses(*)").unwrap_err();
+            assert!(matches!(err, SqlError::AmbiguousRelationship { .. }));
+
+            let result = build_with(
+                cache,
+                "orders",
+                "select=id,billing:addresses!orders_billing_address_id_fkey(city),shipping:addresses!shipping_address_id!inner(city)",
+            )
+            .unwrap();
+            assert!(result
+                .query
+                .contains(r#""orders"."billing_address_id" = "addresses"."id""#));
+            assert!(result
+                .query
+                .contains(r#""orders"."shipping_address_id" = "addresses_2"."id""#));
+            assert!(result.query.contains(r#"AS "billing""#));
+            assert!(result
+                .query
+                .contains(r#"WHERE EXISTS (SELECT 1 FROM "public"."addresses" AS "addresses_2""#));
+        }
+
+        #[test]
+        fn test_embed_json_paths_and_casts() {
+            let result =
+                build("orders", "select=id,customers(metadata->>tier,email::text)").unwrap();
+            assert!(result.query.contains(
+                r#"SELECT "customers"."metadata"->>'tier' AS "tier", "customers"."email"::text AS "email" FROM"#
+            ));
+        }
+
+        #[test]
+        fn test_spread_to_one_flattens_columns() {
+            let result =
+                build("orders", "select=id,...customers(customer_name:name,email)").unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "id", "customers_1".* FROM "orders" LEFT JOIN LATERAL (SELECT "customers"."name" AS "customer_name", "customers"."email" FROM "public"."customers" AS "customers" WHERE "orders"."customer_id" = "customers"."id") AS "customers_1" ON TRUE"#
+            );
+        }
+
+        #[test]
+        fn test_spread_inside_embed() {
+            let result = build(
+                "customers",
+                "select=id,orders(id,...customers(name))&orders.order=id.desc",
+            )
+            .unwrap();
+            assert!(result.query.contains(
+                r#"SELECT "orders"."id", "customers_2_1".* FROM "public"."orders" AS "orders" LEFT JOIN LATERAL (SELECT "customers_2"."name" FROM "public"."customers" AS "customers_2" WHERE "orders"."customer_id" = "customers_2"."id") AS "customers_2_1" ON TRUE WHERE "orders"."customer_id" = "customers"."id" ORDER BY "orders"."id" DESC"#
+            ));
+        }
+
+        #[test]
+        fn test_spread_with_inner_join_and_filters() {
+            let result = build(
+                "orders",
+                "select=id,...customers!inner(name)&customers.name=eq.Ann",
+            )
+            .unwrap();
+            assert!(result.query.ends_with(
+                r#"ON TRUE WHERE EXISTS (SELECT 1 FROM "public"."customers" AS "customers" WHERE "orders"."customer_id" = "customers"."id" AND "customers"."name" = $1)"#
+            ));
+        }
+
+        #[test]
+        fn test_spread_to_many_is_rejected() {
+            let err = build("customers", "select=id,...orders(status)").unwrap_err();
+            assert_eq!(err, SqlError::SpreadToMany("orders".to_string()));
+
+            let err = build("orders", "select=id,...products(name)").unwrap_err();
+            assert_eq!(err, SqlError::SpreadToMany("products".to_string()));
+
+            // Aggregates can only be spread alone: columns next to them would
+            // give one parent row per group
+            for query in [
+                "select=id,...orders(*,total.sum())",
+                "select=id,...orders(status,order_count:id.count())",
+            ] {
+                let err = build("customers", query).unwrap_err();
+                assert_eq!(err, SqlError::SpreadToMany("orders".to_string()));
+            }
+        }
+
+        #[test]
+        fn test_spread_to_many_aggregates() {
+            let result = build("customers", "select=id,...orders(total.sum())").unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "id", "orders_1".* FROM "customers" LEFT JOIN LATERAL (SELECT sum("orders"."total") AS "sum" FROM "public"."orders" AS "orders" WHERE "orders"."customer_id" = "customers"."id") AS "orders_1" ON TRUE"#
+            );
+        }
+
+        #[test]
+        fn test_aggregates_group_by_embed_correlation_columns() {
+            let result = build("orders", "select=status,count(),customers(name)").unwrap();
+            assert!(result
+                .query
+                .ends_with(r#"FROM "orders" GROUP BY "status", "orders"."customer_id""#));
+
+            let result = build("customers", "select=count(),orders(id)").unwrap();
+            assert!(result.query.ends_with(r#"GROUP BY "customers"."id""#));
+        }
+
+        #[test]
+        fn test_aggregates_group_by_spread_columns() {
+            let result = build(
+                "orders",
+                "select=total.sum(),...customers(customer_name:name)",
+            )
+            .unwrap();
+            assert!(result
+                .query
+                .ends_with(r#"AS "customers_1" ON TRUE GROUP BY "customers_1"."customer_name""#));
+
+            // Spread columns that are not known by name cannot be grouped by
+            for query in [
+                "select=total.sum(),...customers(*)",
+                "select=total.sum(),...customers(name,orders(id))",
+            ] {
+                assert_eq!(
+                    build("orders", query).unwrap_err(),
+                    SqlError::UngroupableRelation("customers".to_string())
+                );
+            }
+        }
+
+        #[test]
+        fn test_embed_nested_relation_not_found() {
+            let err = build("customers", "select=orders(missing(id))").unwrap_err();
+            assert!(matches!(
+                err,
+                SqlError::RelationNotFound { ref from_table, ref to_table }
+                    if from_table == "orders" && to_table == "missing"
+            ));
+        }
+    }
 }
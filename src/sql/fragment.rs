@@ -0,0 +1,227 @@
+//! SQL text fragments built from user input.
+//!
+//! Filter values always travel as `$n` parameters, but identifiers, JSON keys,
+//! type names and text search configurations have to be written into the query
+//! text. Every one of them goes through a type in this module, which guarantees
+//! the fragment cannot leave its context:
+//!
+//! - [`Ident`] and [`QualifiedIdent`] double embedded `"` inside a quoted identifier
+//! - [`Literal`] doubles embedded `'` (and backslashes, using an `E''` string)
+//! - [`TypeName`] only accepts the shape of a PostgreSQL type name
+//!
+//! # Examples
+//!
+//! ```
+//! use postgrest_parser::sql::fragment::{Ident, Literal, QualifiedIdent, TypeName};
+//!
+//! assert_eq!(Ident(r#"my "col""#).to_string(), r#""my ""col""""#);
+//! assert_eq!(QualifiedIdent("auth", "users").to_string(), r#""auth"."users""#);
+//! assert_eq!(Literal("it's").to_string(), "'it''s'");
+//! assert_eq!(TypeName::parse("numeric(10,2)[]").unwrap().to_string(), "numeric(10,2)[]");
+//! assert!(TypeName::parse("int; DROP TABLE users").is_err());
+//! ```
+
+use crate::error::SqlError;
+use std::fmt;
+
+/// A quoted identifier: `"name"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ident<'a>(pub &'a str);
+
+impl fmt::Display for Ident<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0.replace('"', "\"\""))
+    }
+}
+
+/// A schema-qualified identifier: `"schema"."name"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QualifiedIdent<'a>(pub &'a str, pub &'a str);
+
+impl fmt::Display for QualifiedIdent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", Ident(self.0), Ident(self.1))
+    }
+}
+
+/// A string constant, used for JSON keys and text search configurations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Literal<'a>(pub &'a str);
+
+impl fmt::Display for Literal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quoted = self.0.replace('\'', "''");
+        if quoted.contains('\\') {
+            // Escape string syntax reads backslashes the same way whatever
+            // standard_conforming_strings is set to
+            write!(f, "E'{}'", quoted.replace('\\', "\\\\"))
+        } else {
+            write!(f, "'{}'", quoted)
+        }
+    }
+}
+
+/// Type names made of several keywords, which cannot be written as one identifier
+const MULTI_WORD_TYPES: &[&str] = &[
+    "bit varying",
+    "character varying",
+    "double precision",
+    "time with time zone",
+    "time without time zone",
+    "timestamp with time zone",
+    "timestamp without time zone",
+];
+
+/// A validated type name, as used by casts: `int`, `public.my_type`,
+/// `character varying(20)`, `numeric(10,2)[]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeName<'a>(&'a str);
+
+impl<'a> TypeName<'a> {
+    /// Accepts a (possibly schema-qualified) type name or one of the multi-word
+    /// built-in types, followed by optional numeric modifiers and `[]` suffixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqlError::InvalidTypeCastForSql`] for anything else.
+    pub fn parse(name: &'a str) -> Result<Self, SqlError> {
+        let rest = match MULTI_WORD_TYPES
+            .iter()
+            .find(|word| starts_with_ignore_case(name, word))
+        {
+            Some(word) => &name[word.len()..],
+            None => skip_qualified_name(name).ok_or(SqlError::InvalidTypeCastForSql)?,
+        };
+        let rest = skip_modifiers(rest).ok_or(SqlError::InvalidTypeCastForSql)?;
+        let rest = skip_array_suffixes(rest);
+
+        if rest.is_empty() {
+            Ok(Self(name))
+        } else {
+            Err(SqlError::InvalidTypeCastForSql)
+        }
+    }
+}
+
+impl fmt::Display for TypeName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+}
+
+/// Skips `name` or `schema.name`, returning the remaining text
+fn skip_qualified_name(text: &str) -> Option<&str> {
+    let rest = skip_word(text)?;
+    match rest.strip_prefix('.') {
+        Some(after_dot) => skip_word(after_dot),
+        None => Some(rest),
+    }
+}
+
+/// Skips an unquoted identifier: a letter or `_` followed by letters, digits and `_`
+fn skip_word(text: &str) -> Option<&str> {
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return None,
+    }
+    let end = chars
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map_or(text.len(), |(i, _)| i);
+    Some(&text[end..])
+}
+
+/// Skips an optional `(n)` or `(n,m)` type modifier
+fn skip_modifiers(text: &str) -> Option<&str> {
+    let Some(inner) = text.strip_prefix('(') else {
+        return Some(text);
+    };
+    let (modifiers, rest) = inner.split_once(')')?;
+    let valid = modifiers.split(',').count() <= 2
+        && modifiers
+            .split(',')
+            .all(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+    valid.then_some(rest)
+}
+
+fn skip_array_suffixes(mut text: &str) -> &str {
+    while let Some(rest) = text.strip_prefix("[]") {
+        text = rest;
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ident_doubles_quotes() {
+        assert_eq!(Ident("id").to_string(), r#""id""#);
+        assert_eq!(Ident(r#"a"b"#).to_string(), r#""a""b""#);
+        assert_eq!(Ident(r#"x" OR 1=1 --"#).to_string(), r#""x"" OR 1=1 --""#);
+    }
+
+    #[test]
+    fn test_qualified_ident() {
+        assert_eq!(
+            QualifiedIdent("my\"schema", "t").to_string(),
+            r#""my""schema"."t""#
+        );
+    }
+
+    #[test]
+    fn test_literal_escaping() {
+        assert_eq!(Literal("english").to_string(), "'english'");
+        assert_eq!(Literal("it's").to_string(), "'it''s'");
+        assert_eq!(Literal(r"a\'b").to_string(), r"E'a\\''b'");
+    }
+
+    #[test]
+    fn test_type_name_accepts_postgres_types() {
+        for name in [
+            "int",
+            "int4",
+            "text[]",
+            "numeric(10,2)",
+            "varchar(255)",
+            "public.my_type",
+            "double precision",
+            "character varying(20)[]",
+            "TIMESTAMP WITH TIME ZONE",
+            "_int4",
+        ] {
+            assert_eq!(TypeName::parse(name).unwrap().to_string(), name);
+        }
+    }
+
+    #[test]
+    fn test_type_name_rejects_everything_else() {
+        for name in [
+            "",
+            "1int",
+            "int;",
+            "text) FROM users --",
+            "text FROM users",
+            "int/**/",
+            "numeric(10,2",
+            "numeric(a)",
+            "numeric(1,2,3)",
+            "a.b.c",
+            "\"quoted\"",
+            "int ",
+            "text[",
+        ] {
+            assert_eq!(
+                TypeName::parse(name),
+                Err(SqlError::InvalidTypeCastForSql),
+                "{name}"
+            );
+        }
+    }
+}
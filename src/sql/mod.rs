@@ -1,5 +1,6 @@
 pub mod builder;
+pub mod fragment;
 pub mod mutation;
 pub mod rpc;
 
-pub use builder::{QueryBuilder, QueryResult};
+pub use builder::{CountQuery, QueryBuilder, QueryResult, DEFAULT_ESTIMATED_COUNT_THRESHOLD};
@@ -1,9 +1,12 @@
 use crate::ast::{
-    ConflictAction, DeleteParams, InsertParams, InsertValues, OnConflict, ResolvedTable,
-    SelectItem, UpdateParams,
+    ConflictAction, DeleteParams, InsertParams, InsertValues, ItemType, LogicCondition, Missing,
+    OnConflict, OrderTerm, Resolution, ResolvedTable, ReturnRepresentation, SelectItem,
+    UpdateParams,
 };
 use crate::error::SqlError;
+use crate::sql::fragment::Ident;
 use crate::sql::{QueryBuilder, QueryResult};
+use std::collections::BTreeMap;
 
 impl QueryBuilder {
     /// Builds an INSERT query with schema-qualified table name
@@ -35,7 +38,7 @@ impl QueryBuilder {
             if i > 0 {
                 self.sql.push_str(", ");
             }
-            self.sql.push_str(&format!("\"{}\"", col));
+            self.sql.push_str(&Ident(col).to_string());
         }
         self.sql.push(')');
 
@@ -43,19 +46,31 @@ impl QueryBuilder {
         self.build_values_clause(&params.values, &columns)?;
 
         // ON CONFLICT clause
-        if let Some(ref on_conflict) = params.on_conflict {
-            self.build_on_conflict_clause(on_conflict)?;
+        let on_conflict = self.resolve_on_conflict(resolved_table, params.on_conflict.as_ref());
+        if let Some(ref on_conflict) = on_conflict {
+            self.build_on_conflict_clause(on_conflict, &columns)?;
         }
 
         // RETURNING clause
-        if let Some(ref returning) = params.returning {
-            self.build_returning_clause(returning)?;
+        if let Some(returning) = self.returning_items(resolved_table, params.returning.as_deref()) {
+            self.build_returning(resolved_table, &returning)?;
+        }
+
+        let mut preference_applied = self.applied_preferences();
+        if let Some(missing) = self.missing {
+            preference_applied.push(missing.as_preference().to_string());
+        }
+        if let (Some(resolution), Some(_)) = (self.resolution, &on_conflict) {
+            preference_applied.push(resolution.as_preference().to_string());
         }
 
         Ok(QueryResult {
             query: self.sql.clone(),
             params: self.params.clone(),
             tables: self.tables.clone(),
+            count: None,
+            wrapped: None,
+            preference_applied,
         })
     }
 
@@ -81,30 +96,21 @@ impl QueryBuilder {
         // SET clause
         self.build_set_clause(&params.set_values)?;
 
-        // WHERE clause
-        if !params.filters.is_empty() {
-            self.build_where_clause(&params.filters)?;
-        }
-
-        // ORDER BY clause
-        if !params.order.is_empty() {
-            self.build_order_clause(&params.order)?;
-        }
-
-        // LIMIT clause
-        if let Some(limit) = params.limit {
-            self.build_limit_clause(limit)?;
-        }
+        // WHERE clause, selecting the limited rows when there is a LIMIT
+        self.build_mutation_where(resolved_table, &params.filters, &params.order, params.limit)?;
 
         // RETURNING clause
-        if let Some(ref returning) = params.returning {
-            self.build_returning_clause(returning)?;
+        if let Some(returning) = self.returning_items(resolved_table, params.returning.as_deref()) {
+            self.build_returning(resolved_table, &returning)?;
         }
 
         Ok(QueryResult {
             query: self.sql.clone(),
             params: self.params.clone(),
             tables: self.tables.clone(),
+            count: None,
+            wrapped: None,
+            preference_applied: self.applied_preferences(),
         })
     }
 
@@ -123,30 +129,21 @@ impl QueryBuilder {
         self.sql
             .push_str(&format!("DELETE FROM {}", resolved_table.qualified_name()));
 
-        // WHERE clause
-        if !params.filters.is_empty() {
-            self.build_where_clause(&params.filters)?;
-        }
-
-        // ORDER BY clause
-        if !params.order.is_empty() {
-            self.build_order_clause(&params.order)?;
-        }
-
-        // LIMIT clause
-        if let Some(limit) = params.limit {
-            self.build_limit_clause(limit)?;
-        }
+        // WHERE clause, selecting the limited rows when there is a LIMIT
+        self.build_mutation_where(resolved_table, &params.filters, &params.order, params.limit)?;
 
         // RETURNING clause
-        if let Some(ref returning) = params.returning {
-            self.build_returning_clause(returning)?;
+        if let Some(returning) = self.returning_items(resolved_table, params.returning.as_deref()) {
+            self.build_returning(resolved_table, &returning)?;
         }
 
         Ok(QueryResult {
             query: self.sql.clone(),
             params: self.params.clone(),
             tables: self.tables.clone(),
+            count: None,
+            wrapped: None,
+            preference_applied: self.applied_preferences(),
         })
     }
 
@@ -159,32 +156,14 @@ impl QueryBuilder {
 
         match values {
             InsertValues::Single(map) => {
-                self.sql.push('(');
-                for (i, col) in columns.iter().enumerate() {
-                    if i > 0 {
-                        self.sql.push_str(", ");
-                    }
-                    let value = map.get(col).unwrap_or(&serde_json::Value::Null);
-                    let param = self.add_param(value.clone());
-                    self.sql.push_str(&param);
-                }
-                self.sql.push(')');
+                self.build_values_row(map, columns);
             }
             InsertValues::Bulk(rows) => {
                 for (row_idx, row) in rows.iter().enumerate() {
                     if row_idx > 0 {
                         self.sql.push_str(", ");
                     }
-                    self.sql.push('(');
-                    for (i, col) in columns.iter().enumerate() {
-                        if i > 0 {
-                            self.sql.push_str(", ");
-                        }
-                        let value = row.get(col).unwrap_or(&serde_json::Value::Null);
-                        let param = self.add_param(value.clone());
-                        self.sql.push_str(&param);
-                    }
-                    self.sql.push(')');
+                    self.build_values_row(row, columns);
                 }
             }
         }
@@ -192,13 +171,77 @@ impl QueryBuilder {
         Ok(())
     }
 
-    fn build_on_conflict_clause(&mut self, on_conflict: &OnConflict) -> Result<(), SqlError> {
+    /// Builds one `(...)` row of a VALUES clause. Columns the row has no key for
+    /// are NULL, or `DEFAULT` with `Prefer: missing=default`.
+    fn build_values_row(
+        &mut self,
+        row: &std::collections::HashMap<String, serde_json::Value>,
+        columns: &[String],
+    ) {
+        self.sql.push('(');
+        for (i, col) in columns.iter().enumerate() {
+            if i > 0 {
+                self.sql.push_str(", ");
+            }
+            let value_sql = match row.get(col) {
+                Some(value) => self.add_param(value.clone()),
+                None if self.missing == Some(Missing::Default) => "DEFAULT".to_string(),
+                None => self.add_param(serde_json::Value::Null),
+            };
+            self.sql.push_str(&value_sql);
+        }
+        self.sql.push(')');
+    }
+
+    /// The ON CONFLICT clause of an INSERT.
+    ///
+    /// With `Prefer: resolution=...` the action follows the preference and the
+    /// target is the `on_conflict=` columns, falling back to the primary key;
+    /// the preference is dropped when neither is known. Otherwise `on_conflict=`
+    /// is used as parsed.
+    fn resolve_on_conflict(
+        &self,
+        resolved_table: &ResolvedTable,
+        requested: Option<&OnConflict>,
+    ) -> Option<OnConflict> {
+        let Some(resolution) = self.resolution else {
+            return requested.cloned();
+        };
+        let action = match resolution {
+            Resolution::MergeDuplicates => ConflictAction::DoUpdate,
+            Resolution::IgnoreDuplicates => ConflictAction::DoNothing,
+        };
+
+        let on_conflict = match requested {
+            Some(requested) => requested.clone(),
+            None => {
+                let key = self.primary_key(resolved_table);
+                if key.is_empty() {
+                    return None;
+                }
+                OnConflict::do_nothing(key)
+            }
+        };
+        Some(OnConflict {
+            action,
+            ..on_conflict
+        })
+    }
+
+    /// Builds `ON CONFLICT (...) DO ...`. Unless `update_columns` says otherwise,
+    /// DO UPDATE sets every inserted column outside the conflict target, or the
+    /// target itself when there is no other, so the conflicting row is returned.
+    fn build_on_conflict_clause(
+        &mut self,
+        on_conflict: &OnConflict,
+        columns: &[String],
+    ) -> Result<(), SqlError> {
         self.sql.push_str(" ON CONFLICT (");
         for (i, col) in on_conflict.columns.iter().enumerate() {
             if i > 0 {
                 self.sql.push_str(", ");
             }
-            self.sql.push_str(&format!("\"{}\"", col));
+            self.sql.push_str(&Ident(col).to_string());
         }
         self.sql.push(')');
 
@@ -226,8 +269,17 @@ impl QueryBuilder {
                     // Use specified columns
                     update_cols.clone()
                 } else {
-                    // Default: update all columns (same as conflict columns for now)
-                    on_conflict.columns.clone()
+                    // Default: every inserted column the conflict target does not cover
+                    let merged: Vec<String> = columns
+                        .iter()
+                        .filter(|col| !on_conflict.columns.contains(col))
+                        .cloned()
+                        .collect();
+                    if merged.is_empty() {
+                        on_conflict.columns.clone()
+                    } else {
+                        merged
+                    }
                 };
 
                 // Update specified columns
@@ -237,7 +289,7 @@ impl QueryBuilder {
                         self.sql.push_str(", ");
                     }
                     self.sql
-                        .push_str(&format!("\"{}\" = EXCLUDED.\"{}\"", col, col));
+                        .push_str(&format!("{} = EXCLUDED.{}", Ident(col), Ident(col)));
                     first = false;
                 }
             }
@@ -261,34 +313,157 @@ impl QueryBuilder {
             }
             let value = set_values.get(*key).unwrap();
             let param = self.add_param(value.clone());
-            self.sql.push_str(&format!("\"{}\" = {}", key, param));
+            self.sql.push_str(&format!("{} = {}", Ident(key), param));
+        }
+
+        Ok(())
+    }
+
+    /// The items a mutation returns, given its `select` list and the
+    /// `Prefer: return=...` preference (see [`QueryBuilder::with_return`])
+    fn returning_items(
+        &self,
+        resolved_table: &ResolvedTable,
+        requested: Option<&[SelectItem]>,
+    ) -> Option<Vec<SelectItem>> {
+        match self.return_representation {
+            None => requested.map(<[SelectItem]>::to_vec),
+            Some(ReturnRepresentation::Full) => {
+                Some(requested.map_or_else(|| vec![SelectItem::field("*")], <[SelectItem]>::to_vec))
+            }
+            Some(ReturnRepresentation::Minimal) => None,
+            Some(ReturnRepresentation::HeadersOnly) => {
+                let key = self.primary_key(resolved_table);
+                (!key.is_empty()).then(|| key.into_iter().map(SelectItem::field).collect())
+            }
+        }
+    }
+
+    /// Preferences applied to a mutation, for the `Preference-Applied` header
+    fn applied_preferences(&self) -> Vec<String> {
+        self.return_representation
+            .iter()
+            .map(|representation| representation.as_preference().to_string())
+            .collect()
+    }
+
+    /// Builds the RETURNING part of a mutation.
+    ///
+    /// Columns, with their JSON paths and casts, go into a plain RETURNING
+    /// clause. Embedded resources, spreads and aggregates need the full select
+    /// list, so the mutation becomes a CTE returning whole rows, which is then
+    /// selected from like the table itself:
+    /// `WITH "pgrst_source" AS (... RETURNING *) SELECT ... FROM "pgrst_source" AS "table"`.
+    fn build_returning(
+        &mut self,
+        resolved_table: &ResolvedTable,
+        items: &[SelectItem],
+    ) -> Result<(), SqlError> {
+        let plain = items
+            .iter()
+            .all(|item| item.item_type == ItemType::Field && !item.is_aggregate());
+        if plain {
+            return self.build_returning_clause(items);
+        }
+
+        let mutation = std::mem::take(&mut self.sql);
+        // Embeds are resolved and correlated against the mutated table
+        self.current_schema = resolved_table.schema.clone();
+        let (joins, conditions, group_by) = self.build_select_list(items, &BTreeMap::new(), &[])?;
+        let select = std::mem::take(&mut self.sql);
+        let source = Ident("pgrst_source");
+
+        self.sql = format!(
+            "WITH {} AS ({} RETURNING *) {} FROM {} AS {}{}",
+            source,
+            mutation,
+            select,
+            source,
+            Ident(&resolved_table.name),
+            joins
+        );
+        if !conditions.is_empty() {
+            self.sql.push_str(" WHERE ");
+            self.sql.push_str(&conditions.join(" AND "));
         }
+        self.sql.push_str(&group_by);
 
         Ok(())
     }
 
     fn build_returning_clause(&mut self, items: &[SelectItem]) -> Result<(), SqlError> {
+        let columns = items
+            .iter()
+            .map(|item| self.select_field_to_sql(None, item))
+            .collect::<Result<Vec<_>, _>>()?;
+
         self.sql.push_str(" RETURNING ");
+        self.sql.push_str(&columns.join(", "));
+        Ok(())
+    }
 
-        for (i, item) in items.iter().enumerate() {
-            if i > 0 {
-                self.sql.push_str(", ");
+    /// Builds the WHERE clause of an UPDATE or DELETE.
+    ///
+    /// PostgreSQL has no ORDER BY or LIMIT for these statements, so, like PostgREST,
+    /// a limited mutation picks its rows with a subquery: by primary key when the
+    /// schema cache knows it, by `ctid` otherwise. Ordering alone has no effect.
+    fn build_mutation_where(
+        &mut self,
+        resolved_table: &ResolvedTable,
+        filters: &[LogicCondition],
+        order: &[OrderTerm],
+        limit: Option<u64>,
+    ) -> Result<(), SqlError> {
+        let Some(limit) = limit else {
+            if !filters.is_empty() {
+                self.build_where_clause(filters)?;
             }
+            return Ok(());
+        };
 
-            // Relations are not supported in RETURNING for now
-            if matches!(item.item_type, crate::ast::ItemType::Relation) {
-                return Err(SqlError::FailedToBuildSelectClause);
-            }
+        let key = self.primary_key(resolved_table);
+        let columns = if key.is_empty() {
+            "ctid".to_string()
+        } else {
+            key.iter()
+                .map(|column| Ident(column).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
 
-            self.sql.push_str(&format!("\"{}\"", item.name));
-            if let Some(ref alias) = item.alias {
-                self.sql.push_str(&format!(" AS \"{}\"", alias));
-            }
+        self.sql.push_str(" WHERE ");
+        match key.len() {
+            0 => self.sql.push_str("ctid = ANY(ARRAY("),
+            1 => self.sql.push_str(&format!("{} IN (", columns)),
+            _ => self.sql.push_str(&format!("({}) IN (", columns)),
+        }
+        self.sql.push_str(&format!(
+            "SELECT {} FROM {}",
+            columns,
+            resolved_table.qualified_name()
+        ));
+        if !filters.is_empty() {
+            self.build_where_clause(filters)?;
         }
+        self.build_order_clause(order)?;
+        self.build_limit_clause(limit)?;
+        self.sql.push_str(if key.is_empty() { "))" } else { ")" });
 
         Ok(())
     }
 
+    /// Primary key columns of a table, empty when unknown
+    #[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+    fn primary_key(&self, resolved_table: &ResolvedTable) -> Vec<String> {
+        #[cfg(feature = "postgres")]
+        if let Some(cache) = &self.schema_cache {
+            if let Some(key) = cache.get_primary_key(&resolved_table.schema, &resolved_table.name) {
+                return key.to_vec();
+            }
+        }
+        Vec::new()
+    }
+
     fn build_limit_clause(&mut self, limit: u64) -> Result<(), SqlError> {
         let param = self.add_param(serde_json::Value::Number(limit.into()));
         self.sql.push_str(&format!(" LIMIT {}", param));
@@ -469,4 +644,343 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), SqlError::LimitWithoutOrder));
     }
+
+    fn status_filter() -> LogicCondition {
+        LogicCondition::Filter(crate::ast::Filter::new(
+            Field::new("status"),
+            crate::ast::FilterOperator::Eq,
+            crate::ast::FilterValue::Single("pending".to_string()),
+        ))
+    }
+
+    #[test]
+    fn test_update_with_order_and_limit_selects_rows_by_ctid() {
+        let mut builder = QueryBuilder::new();
+        let table = ResolvedTable::new("public", "jobs");
+
+        let set_values = HashMap::from([("status".to_string(), json!("running"))]);
+        let params = UpdateParams::new(set_values)
+            .with_filters(vec![status_filter()])
+            .with_order(crate::parser::parse_order("created_at.asc").unwrap())
+            .with_limit(10);
+
+        let result = builder.build_update(&table, &params).unwrap();
+        assert_eq!(
+            result.query,
+            r#"UPDATE "public"."jobs" SET "status" = $1 WHERE ctid = ANY(ARRAY(SELECT ctid FROM "public"."jobs" WHERE "status" = $2 ORDER BY "created_at" ASC LIMIT $3))"#
+        );
+        assert_eq!(
+            result.params,
+            vec![json!("running"), json!("pending"), json!(10)]
+        );
+    }
+
+    #[test]
+    fn test_delete_with_order_and_limit() {
+        let mut builder = QueryBuilder::new();
+        let table = ResolvedTable::new("public", "jobs");
+
+        let params = DeleteParams::new()
+            .with_filters(vec![status_filter()])
+            .with_order(crate::parser::parse_order("id.desc").unwrap())
+            .with_limit(1)
+            .with_returning(vec![SelectItem::field("id")]);
+
+        let result = builder.build_delete(&table, &params).unwrap();
+        assert_eq!(
+            result.query,
+            r#"DELETE FROM "public"."jobs" WHERE ctid = ANY(ARRAY(SELECT ctid FROM "public"."jobs" WHERE "status" = $1 ORDER BY "id" DESC LIMIT $2)) RETURNING "id""#
+        );
+    }
+
+    #[test]
+    fn test_order_without_limit_is_ignored() {
+        let mut builder = QueryBuilder::new();
+        let table = ResolvedTable::new("public", "jobs");
+
+        let params = DeleteParams::new()
+            .with_filters(vec![status_filter()])
+            .with_order(crate::parser::parse_order("id.desc").unwrap());
+
+        let result = builder.build_delete(&table, &params).unwrap();
+        assert_eq!(
+            result.query,
+            r#"DELETE FROM "public"."jobs" WHERE "status" = $1"#
+        );
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_limited_mutations_use_known_primary_key() {
+        use crate::schema_cache::SchemaCache;
+        use std::sync::Arc;
+
+        let mut cache = SchemaCache::new();
+        cache.add_primary_key("public", "jobs", vec!["id".to_string()]);
+        cache.add_primary_key(
+            "public",
+            "tenant_jobs",
+            vec!["tenant_id".to_string(), "id".to_string()],
+        );
+        let cache = Arc::new(cache);
+
+        let params = DeleteParams::new()
+            .with_filters(vec![status_filter()])
+            .with_order(crate::parser::parse_order("id.desc").unwrap())
+            .with_limit(5);
+
+        let result = QueryBuilder::new()
+            .with_schema_cache(cache.clone())
+            .build_delete(&ResolvedTable::new("public", "jobs"), &params)
+            .unwrap();
+        assert_eq!(
+            result.query,
+            r#"DELETE FROM "public"."jobs" WHERE "id" IN (SELECT "id" FROM "public"."jobs" WHERE "status" = $1 ORDER BY "id" DESC LIMIT $2)"#
+        );
+
+        let result = QueryBuilder::new()
+            .with_schema_cache(cache)
+            .build_delete(&ResolvedTable::new("public", "tenant_jobs"), &params)
+            .unwrap();
+        assert!(result.query.starts_with(
+            r#"DELETE FROM "public"."tenant_jobs" WHERE ("tenant_id", "id") IN (SELECT "tenant_id", "id" FROM "public"."tenant_jobs" WHERE"#
+        ));
+    }
+
+    #[test]
+    fn test_returning_json_paths_and_casts() {
+        let mut builder = QueryBuilder::new();
+        let table = ResolvedTable::new("public", "users");
+
+        let values = HashMap::from([("name".to_string(), json!("Alice"))]);
+        let returning = crate::parser::parse_select("id::text,settings->>theme").unwrap();
+        let params = InsertParams::new(InsertValues::Single(values)).with_returning(returning);
+
+        let result = builder.build_insert(&table, &params).unwrap();
+        assert_eq!(
+            result.query,
+            r#"INSERT INTO "public"."users" ("name") VALUES ($1) RETURNING "id"::text AS "id", "settings"->>'theme' AS "theme""#
+        );
+    }
+
+    #[test]
+    fn test_returning_star() {
+        let mut builder = QueryBuilder::new();
+        let table = ResolvedTable::new("public", "users");
+
+        let params = DeleteParams::new()
+            .with_filters(vec![status_filter()])
+            .with_returning(vec![SelectItem::field("*")]);
+
+        let result = builder.build_delete(&table, &params).unwrap();
+        assert!(result.query.ends_with(" RETURNING *"));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_returning_embeds_wraps_mutation_in_cte() {
+        use crate::schema_cache::{ForeignKey, SchemaCache};
+        use std::sync::Arc;
+
+        let mut cache = SchemaCache::new();
+        cache.add_foreign_key(ForeignKey {
+            from_schema: "sales".to_string(),
+            from_table: "orders".to_string(),
+            from_columns: vec!["customer_id".to_string()],
+            to_schema: "sales".to_string(),
+            to_table: "customers".to_string(),
+            to_columns: vec!["id".to_string()],
+            constraint_name: "orders_customer_id_fkey".to_string(),
+        });
+
+        let values = HashMap::from([("customer_id".to_string(), json!(1))]);
+        let returning = crate::parser::parse_select("id,customers(name)").unwrap();
+        let params = InsertParams::new(InsertValues::Single(values)).with_returning(returning);
+
+        let result = QueryBuilder::new()
+            .with_schema_cache(Arc::new(cache))
+            .build_insert(&ResolvedTable::new("sales", "orders"), &params)
+            .unwrap();
+        assert_eq!(
+            result.query,
+            concat!(
+                r#"WITH "pgrst_source" AS (INSERT INTO "sales"."orders" ("customer_id") VALUES ($1) RETURNING *) "#,
+                r#"SELECT "id", COALESCE((SELECT row_to_json("customers_1") FROM (SELECT "customers"."name" FROM "sales"."customers" AS "customers" WHERE "orders"."customer_id" = "customers"."id") "customers_1"), 'null'::json) AS "customers" "#,
+                r#"FROM "pgrst_source" AS "orders""#
+            )
+        );
+        assert_eq!(result.params, vec![json!(1)]);
+    }
+
+    #[test]
+    fn test_return_representation_overrides_select() {
+        let table = ResolvedTable::new("public", "users");
+        let values = HashMap::from([("name".to_string(), json!("Alice"))]);
+        let params = InsertParams::new(InsertValues::Single(values))
+            .with_returning(vec![SelectItem::field("id")]);
+
+        let result = QueryBuilder::new()
+            .with_return(ReturnRepresentation::Full)
+            .build_insert(&table, &params)
+            .unwrap();
+        assert!(result.query.ends_with(r#" RETURNING "id""#));
+
+        let result = QueryBuilder::new()
+            .with_return(ReturnRepresentation::Minimal)
+            .build_insert(&table, &params)
+            .unwrap();
+        assert!(!result.query.contains("RETURNING"));
+        assert_eq!(result.preference_applied, vec!["return=minimal"]);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_headers_only_returns_primary_key() {
+        use crate::schema_cache::SchemaCache;
+        use std::sync::Arc;
+
+        let mut cache = SchemaCache::new();
+        cache.add_primary_key(
+            "public",
+            "tenant_projects",
+            vec!["tenant_id".to_string(), "id".to_string()],
+        );
+
+        let values = HashMap::from([("name".to_string(), json!("Apollo"))]);
+        let params = InsertParams::new(InsertValues::Single(values))
+            .with_returning(vec![SelectItem::field("name")]);
+
+        let result = QueryBuilder::new()
+            .with_schema_cache(Arc::new(cache))
+            .with_return(ReturnRepresentation::HeadersOnly)
+            .build_insert(&ResolvedTable::new("public", "tenant_projects"), &params)
+            .unwrap();
+        assert!(result.query.ends_with(r#" RETURNING "tenant_id", "id""#));
+        assert_eq!(result.preference_applied, vec!["return=headers-only"]);
+    }
+
+    #[test]
+    fn test_missing_default_with_columns_param() {
+        let table = ResolvedTable::new("public", "users");
+        let values = HashMap::from([("name".to_string(), json!("Alice"))]);
+        let params = InsertParams::new(InsertValues::Single(values))
+            .with_columns(vec!["name".to_string(), "status".to_string()]);
+
+        let result = QueryBuilder::new()
+            .with_missing(Missing::Default)
+            .build_insert(&table, &params)
+            .unwrap();
+        assert_eq!(
+            result.query,
+            r#"INSERT INTO "public"."users" ("name", "status") VALUES ($1, DEFAULT)"#
+        );
+        assert_eq!(result.params, vec![json!("Alice")]);
+
+        // An explicit null is kept
+        let values = HashMap::from([("name".to_string(), json!(null))]);
+        let params = InsertParams::new(InsertValues::Single(values));
+        let result = QueryBuilder::new()
+            .with_missing(Missing::Default)
+            .build_insert(&table, &params)
+            .unwrap();
+        assert!(result.query.ends_with("VALUES ($1)"));
+    }
+
+    #[test]
+    fn test_do_update_sets_inserted_non_conflict_columns() {
+        let values = HashMap::from([
+            ("email".to_string(), json!("alice@example.com")),
+            ("name".to_string(), json!("Alice")),
+            ("team".to_string(), json!("core")),
+        ]);
+        let params = InsertParams::new(InsertValues::Single(values))
+            .with_on_conflict(OnConflict::do_update(vec!["email".to_string()]));
+
+        let result = QueryBuilder::new()
+            .build_insert(&ResolvedTable::new("public", "users"), &params)
+            .unwrap();
+        assert!(result.query.ends_with(
+            r#"ON CONFLICT ("email") DO UPDATE SET "name" = EXCLUDED."name", "team" = EXCLUDED."team""#
+        ));
+        assert!(result.preference_applied.is_empty());
+    }
+
+    #[test]
+    fn test_resolution_overrides_on_conflict_action() {
+        let table = ResolvedTable::new("public", "users");
+        let values = HashMap::from([
+            ("email".to_string(), json!("alice@example.com")),
+            ("name".to_string(), json!("Alice")),
+        ]);
+        // on_conflict=email parses as DO NOTHING; only its target is kept
+        let params = InsertParams::new(InsertValues::Single(values))
+            .with_on_conflict(OnConflict::do_nothing(vec!["email".to_string()]));
+
+        let result = QueryBuilder::new()
+            .with_resolution(Resolution::MergeDuplicates)
+            .build_insert(&table, &params)
+            .unwrap();
+        assert!(result
+            .query
+            .ends_with(r#"ON CONFLICT ("email") DO UPDATE SET "name" = EXCLUDED."name""#));
+        assert_eq!(
+            result.preference_applied,
+            vec!["resolution=merge-duplicates"]
+        );
+
+        let result = QueryBuilder::new()
+            .with_resolution(Resolution::IgnoreDuplicates)
+            .build_insert(&table, &params)
+            .unwrap();
+        assert!(result
+            .query
+            .ends_with(r#"ON CONFLICT ("email") DO NOTHING"#));
+        assert_eq!(
+            result.preference_applied,
+            vec!["resolution=ignore-duplicates"]
+        );
+    }
+
+    #[test]
+    fn test_resolution_without_conflict_target_is_ignored() {
+        let values = HashMap::from([("name".to_string(), json!("Alice"))]);
+        let params = InsertParams::new(InsertValues::Single(values));
+
+        let result = QueryBuilder::new()
+            .with_resolution(Resolution::MergeDuplicates)
+            .build_insert(&ResolvedTable::new("public", "users"), &params)
+            .unwrap();
+        assert!(!result.query.contains("ON CONFLICT"));
+        assert!(result.preference_applied.is_empty());
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_resolution_targets_primary_key() {
+        use crate::schema_cache::SchemaCache;
+        use std::sync::Arc;
+
+        let mut cache = SchemaCache::new();
+        cache.add_primary_key(
+            "public",
+            "post_tags",
+            vec!["post_id".to_string(), "tag_id".to_string()],
+        );
+
+        let values = HashMap::from([
+            ("post_id".to_string(), json!(1)),
+            ("tag_id".to_string(), json!(2)),
+            ("note".to_string(), json!("pinned")),
+        ]);
+        let params = InsertParams::new(InsertValues::Single(values));
+
+        let result = QueryBuilder::new()
+            .with_schema_cache(Arc::new(cache))
+            .with_resolution(Resolution::MergeDuplicates)
+            .build_insert(&ResolvedTable::new("public", "post_tags"), &params)
+            .unwrap();
+        assert!(result.query.ends_with(
+            r#"ON CONFLICT ("post_id", "tag_id") DO UPDATE SET "note" = EXCLUDED."note""#
+        ));
+    }
 }
@@ -1,12 +1,33 @@
-use crate::ast::{ResolvedTable, RpcParams};
+use crate::ast::{Function, FunctionParam, ResolvedTable, ReturnKind, RpcBody, RpcParams};
 use crate::error::SqlError;
+use crate::sql::fragment::{Ident, QualifiedIdent, TypeName};
 use crate::sql::{QueryBuilder, QueryResult};
+use std::collections::{BTreeSet, HashMap};
+
+/// Alias of the rows a batch body is expanded into
+const BATCH_ARGS_ALIAS: &str = "pgrst_args";
 
 impl QueryBuilder {
     /// Builds an RPC (function call) query with schema-qualified function name
     ///
     /// Generates: SELECT * FROM "schema"."function_name"(arg1 := $1, arg2 := $2)
     /// PostgREST allows filtering, ordering, and pagination of function results
+    ///
+    /// With the function in the schema cache, the query follows its return type:
+    ///
+    /// - `SETOF`/`TABLE` functions stay in FROM, where a set of scalars is aliased
+    ///   to the function name so it can be filtered as a column
+    /// - single scalar and `void` returns are called as `SELECT "schema"."fn"(...) AS "fn"`
+    /// - a single composite value is expanded with `SELECT * FROM "schema"."fn"(...)`
+    ///
+    /// Filtering, ordering or paginating a result that is not a set is an error.
+    ///
+    /// A batch body ([`RpcBody::Batch`]) calls the function once per element in
+    /// a single statement, joining `json_to_recordset` rows LATERALly.
+    ///
+    /// Overloads are told apart by the argument names, as PostgREST does (see
+    /// [`Function::accepts`]), and `VARIADIC` parameters receive their argument
+    /// as an array, e.g. from repeated query keys: `VARIADIC "ids" := $1::integer[]`.
     pub fn build_rpc(
         &mut self,
         resolved_table: &ResolvedTable,
@@ -14,6 +35,33 @@ impl QueryBuilder {
     ) -> Result<QueryResult, SqlError> {
         self.tables.push(params.function_name.clone());
 
+        let function = self.rpc_function(resolved_table, params)?;
+        if let RpcBody::Batch(rows) = &params.body {
+            return self.build_batch_rpc(resolved_table, params, function.as_ref(), rows);
+        }
+
+        let returns = function.as_ref().map(|function| &function.returns);
+        let is_set = returns.is_none_or(|returns| returns.set_of);
+        let is_scalar = returns.is_some_and(|returns| returns.kind != ReturnKind::Composite);
+
+        if !is_set {
+            let paginated = params.limit.is_some() || params.offset.is_some();
+            if !params.filters.is_empty() || !params.order.is_empty() || paginated {
+                return Err(SqlError::FunctionNotSetReturning(
+                    resolved_table.name.clone(),
+                ));
+            }
+
+            if is_scalar {
+                // SELECT "schema"."function_name"(args) AS "function_name"
+                self.sql.push_str("SELECT ");
+                self.build_function_call(resolved_table, params, function.as_ref())?;
+                self.sql
+                    .push_str(&format!(" AS {}", Ident(&resolved_table.name)));
+                return Ok(self.rpc_result());
+            }
+        }
+
         // SELECT clause (use returning if specified, otherwise *)
         if let Some(ref returning) = params.returning {
             self.build_select_clause(returning)?;
@@ -23,29 +71,113 @@ impl QueryBuilder {
 
         // FROM schema.function_name(args)
         self.sql.push_str(" FROM ");
-        self.sql.push_str(&format!(
-            "\"{}\".\"{}\"(",
-            resolved_table.schema, resolved_table.name
-        ));
+        self.build_function_call(resolved_table, params, function.as_ref())?;
+        if is_scalar {
+            // A set of scalars is a single column named after the function
+            self.sql
+                .push_str(&format!(" AS {}", Ident(&resolved_table.name)));
+        }
 
-        // Build named arguments in deterministic order
-        if !params.args.is_empty() {
-            let mut sorted_args: Vec<(&String, &serde_json::Value)> = params.args.iter().collect();
-            sorted_args.sort_by_key(|(k, _)| *k);
+        // WHERE clause for filtering function results
+        if !params.filters.is_empty() {
+            self.build_where_clause(&params.filters)?;
+        }
 
-            for (i, (name, value)) in sorted_args.iter().enumerate() {
-                if i > 0 {
-                    self.sql.push_str(", ");
-                }
-                let param_placeholder = self.add_param((*value).clone());
-                self.sql
-                    .push_str(&format!("\"{}\" := {}", name, param_placeholder));
-            }
+        // ORDER BY clause
+        if !params.order.is_empty() {
+            self.build_order_clause(&params.order)?;
         }
 
-        self.sql.push(')');
+        // LIMIT and OFFSET
+        self.build_limit_offset(params.limit, params.offset)?;
 
-        // WHERE clause for filtering function results
+        Ok(self.rpc_result())
+    }
+
+    /// Builds the calls of a batch body as one set-based statement: the array is
+    /// a single JSON parameter turned into rows by `json_to_recordset`, and the
+    /// function is called for each row through a LATERAL join. Filters, order
+    /// and pagination apply to the combined results.
+    ///
+    /// ```sql
+    /// SELECT * FROM (
+    ///     SELECT "fn".* FROM json_to_recordset($1::json) AS "pgrst_args"("a" integer),
+    ///     LATERAL "schema"."fn"("a" := "pgrst_args"."a") AS "fn"
+    /// ) AS "fn"
+    /// ```
+    ///
+    /// The record definition needs the parameter types, so the function must be
+    /// in the schema cache.
+    fn build_batch_rpc(
+        &mut self,
+        resolved_table: &ResolvedTable,
+        params: &RpcParams,
+        function: Option<&Function>,
+        rows: &[HashMap<String, serde_json::Value>],
+    ) -> Result<QueryResult, SqlError> {
+        let function = function
+            .ok_or_else(|| SqlError::FunctionSignatureRequired(resolved_table.name.clone()))?;
+
+        // Arguments are the union of the keys of every element, all of them
+        // parameters of the resolved overload
+        let names: BTreeSet<&String> = rows.iter().flat_map(|row| row.keys()).collect();
+        let mut columns: Vec<(&FunctionParam, TypeName)> = Vec::new();
+        for param in names.into_iter().filter_map(|name| function.param(name)) {
+            columns.push((param, TypeName::parse(&param.type_)?));
+        }
+
+        let array = rows
+            .iter()
+            .map(|row| serde_json::Value::Object(row.clone().into_iter().collect()))
+            .collect();
+        let array_param = self.add_param(serde_json::Value::Array(array));
+
+        // SELECT clause (use returning if specified, otherwise *)
+        if let Some(ref returning) = params.returning {
+            self.build_select_clause(returning)?;
+        } else {
+            self.sql.push_str("SELECT *");
+        }
+
+        let alias = Ident(&resolved_table.name);
+        let args = Ident(BATCH_ARGS_ALIAS);
+        self.sql
+            .push_str(&format!(" FROM (SELECT {}.* FROM ", alias));
+        if columns.is_empty() {
+            // Elements without keys call the function without arguments
+            self.sql.push_str(&format!(
+                "json_array_elements({}::json) AS {}",
+                array_param, args
+            ));
+        } else {
+            let definition = columns
+                .iter()
+                .map(|(param, type_name)| format!("{} {}", Ident(&param.name), type_name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.sql.push_str(&format!(
+                "json_to_recordset({}::json) AS {}({})",
+                array_param, args, definition
+            ));
+        }
+
+        let call_args = columns
+            .iter()
+            .map(|(param, _)| {
+                let name = Ident(&param.name);
+                format!("{}{} := {}.{}", variadic_prefix(param), name, args, name)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.sql.push_str(&format!(
+            ", LATERAL {}({}) AS {}) AS {}",
+            QualifiedIdent(&resolved_table.schema, &resolved_table.name),
+            call_args,
+            alias,
+            alias
+        ));
+
+        // WHERE clause for filtering the combined results
         if !params.filters.is_empty() {
             self.build_where_clause(&params.filters)?;
         }
@@ -58,18 +190,173 @@ impl QueryBuilder {
         // LIMIT and OFFSET
         self.build_limit_offset(params.limit, params.offset)?;
 
-        Ok(QueryResult {
+        Ok(self.rpc_result())
+    }
+
+    /// Appends `"schema"."function_name"(arg1 := $1, ...)`, or
+    /// `"schema"."function_name"($1)` when the body is the single argument; that
+    /// argument is cast to the parameter's type when the schema cache knows it
+    fn build_function_call(
+        &mut self,
+        resolved_table: &ResolvedTable,
+        params: &RpcParams,
+        function: Option<&Function>,
+    ) -> Result<(), SqlError> {
+        self.sql.push_str(&format!(
+            "{}(",
+            QualifiedIdent(&resolved_table.schema, &resolved_table.name)
+        ));
+
+        let single_arg = match &params.body {
+            // Batch bodies are expanded into rows by build_batch_rpc
+            RpcBody::Named | RpcBody::Batch(_) => None,
+            RpcBody::SingleObject(value) => Some(value.clone()),
+            RpcBody::Raw(_, body) => Some(serde_json::Value::String(body.clone())),
+        };
+
+        if let Some(value) = single_arg {
+            let param_placeholder = self.add_param(value);
+            self.sql.push_str(&param_placeholder);
+            if let Some([param]) = function.map(|function| function.params.as_slice()) {
+                self.sql
+                    .push_str(&format!("::{}", TypeName::parse(&param.type_)?));
+            }
+        } else if !params.args.is_empty() {
+            // Build named arguments in deterministic order
+            let mut sorted_args: Vec<(&String, &serde_json::Value)> = params.args.iter().collect();
+            sorted_args.sort_by_key(|(k, _)| *k);
+
+            for (i, (name, value)) in sorted_args.iter().enumerate() {
+                if i > 0 {
+                    self.sql.push_str(", ");
+                }
+                let variadic = function
+                    .and_then(|function| function.param(name))
+                    .filter(|param| param.variadic);
+                let value = if variadic.is_some() && !value.is_array() {
+                    // A single value is a one-element array
+                    serde_json::Value::Array(vec![(*value).clone()])
+                } else {
+                    (*value).clone()
+                };
+                let param_placeholder = self.add_param(value);
+                self.sql.push_str(&format!(
+                    "{}{} := {}",
+                    variadic.map_or("", variadic_prefix),
+                    Ident(name),
+                    param_placeholder
+                ));
+                // Polymorphic types such as "any" cannot be cast to
+                if let Some(Ok(type_name)) = variadic.map(|param| TypeName::parse(&param.type_)) {
+                    self.sql.push_str(&format!("::{}", type_name));
+                }
+            }
+        }
+
+        self.sql.push(')');
+        Ok(())
+    }
+
+    /// The metadata of the called overload, when the schema cache knows the
+    /// function, see [`select_overload`]
+    #[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+    fn rpc_function(
+        &self,
+        resolved_table: &ResolvedTable,
+        params: &RpcParams,
+    ) -> Result<Option<Function>, SqlError> {
+        #[cfg(feature = "postgres")]
+        if let Some(cache) = &self.schema_cache {
+            let candidates = cache.get_functions(&resolved_table.schema, &resolved_table.name);
+            if !candidates.is_empty() {
+                return select_overload(&resolved_table.name, candidates, params)
+                    .map(|function| Some(function.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn rpc_result(&self) -> QueryResult {
+        QueryResult {
             query: self.sql.clone(),
             params: self.params.clone(),
             tables: self.tables.clone(),
+            count: None,
+            wrapped: None,
+            preference_applied: Vec::new(),
+        }
+    }
+}
+
+/// Picks the overload a call reaches, as PostgREST does:
+///
+/// - named arguments, or the keys of the elements of a batch, must be accepted
+///   by the overload (see [`Function::accepts`])
+/// - a single-object body needs one `json` or `jsonb` parameter, a raw body a
+///   single unnamed parameter of the type its media type is passed to (`text`
+///   for `text/plain`, `bytea` for `application/octet-stream`)
+///
+/// No match, or more than one, is an error listing the candidate signatures.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+fn select_overload<'a>(
+    function_name: &str,
+    candidates: &'a [Function],
+    params: &RpcParams,
+) -> Result<&'a Function, SqlError> {
+    let arg_names: BTreeSet<&str> = match &params.body {
+        RpcBody::Batch(rows) => rows
+            .iter()
+            .flat_map(|row| row.keys())
+            .map(String::as_str)
+            .collect(),
+        _ => params.args.keys().map(String::as_str).collect(),
+    };
+    let arg_names: Vec<&str> = arg_names.into_iter().collect();
+
+    let matches: Vec<&Function> = candidates
+        .iter()
+        .filter(|function| match &params.body {
+            RpcBody::Named | RpcBody::Batch(_) => function.accepts(&arg_names),
+            RpcBody::SingleObject(_) => matches!(
+                function.params.as_slice(),
+                [param] if matches!(param.type_.as_str(), "json" | "jsonb")
+            ),
+            RpcBody::Raw(media_type, _) => function
+                .single_unnamed_param()
+                .is_some_and(|param| param.type_ == media_type.param_type()),
         })
+        .collect();
+
+    match matches.as_slice() {
+        [function] => Ok(function),
+        [] => Err(SqlError::FunctionNotFound {
+            function: function_name.to_string(),
+            arguments: arg_names.iter().map(|name| name.to_string()).collect(),
+            candidates: candidates.iter().map(Function::signature).collect(),
+        }),
+        _ => Err(SqlError::AmbiguousFunction {
+            function: function_name.to_string(),
+            candidates: matches
+                .iter()
+                .map(|function| function.signature())
+                .collect(),
+        }),
+    }
+}
+
+/// `VARIADIC ` before the argument of a variadic parameter
+fn variadic_prefix(param: &FunctionParam) -> &'static str {
+    if param.variadic {
+        "VARIADIC "
+    } else {
+        ""
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ast::{LogicCondition, ResolvedTable, RpcParams};
+    use crate::ast::{LogicCondition, RawMediaType, ResolvedTable, RpcParams};
     use crate::parser::{parse_filter, parse_order};
     use serde_json::Value;
     use std::collections::HashMap;
@@ -192,4 +479,344 @@ mod tests {
         assert!(result.query.contains("LIMIT"));
         assert!(result.params.len() >= 3);
     }
+
+    #[test]
+    fn test_build_rpc_single_argument_bodies() {
+        let resolved = ResolvedTable::new("public", "ingest");
+
+        let params = RpcParams::new("ingest", HashMap::new())
+            .with_body(RpcBody::SingleObject(serde_json::json!({"kind": "click"})));
+        let result = QueryBuilder::new().build_rpc(&resolved, &params).unwrap();
+        assert_eq!(result.query, r#"SELECT * FROM "public"."ingest"($1)"#);
+        assert_eq!(result.params, vec![serde_json::json!({"kind": "click"})]);
+
+        let params = RpcParams::new("ingest", HashMap::new())
+            .with_body(RpcBody::Raw(RawMediaType::Text, "plain text".to_string()));
+        let result = QueryBuilder::new().build_rpc(&resolved, &params).unwrap();
+        assert_eq!(result.query, r#"SELECT * FROM "public"."ingest"($1)"#);
+        assert_eq!(result.params, vec![Value::String("plain text".to_string())]);
+    }
+
+    #[test]
+    fn test_build_batch_rpc_requires_signature() {
+        let rows = vec![HashMap::from([("a".to_string(), Value::Number(1.into()))])];
+        let params = RpcParams::new("add", HashMap::new()).with_body(RpcBody::Batch(rows));
+
+        let result = QueryBuilder::new().build_rpc(&ResolvedTable::new("public", "add"), &params);
+        assert_eq!(
+            result.unwrap_err(),
+            SqlError::FunctionSignatureRequired("add".to_string())
+        );
+    }
+
+    #[cfg(feature = "postgres")]
+    mod with_schema_cache {
+        use super::*;
+        use crate::ast::{Function, FunctionParam, FunctionReturn};
+        use crate::schema_cache::SchemaCache;
+        use serde_json::json;
+        use std::sync::Arc;
+
+        fn build(
+            returns: FunctionReturn,
+            params: &RpcParams,
+        ) -> Result<crate::sql::QueryResult, SqlError> {
+            let mut cache = SchemaCache::new();
+            cache.add_function(
+                Function::new("public", "stats")
+                    .with_params(vec![FunctionParam::new("category", "text")])
+                    .returning(returns),
+            );
+            QueryBuilder::new()
+                .with_schema_cache(Arc::new(cache))
+                .build_rpc(&ResolvedTable::new("public", "stats"), params)
+        }
+
+        fn call() -> RpcParams {
+            RpcParams::new(
+                "stats",
+                HashMap::from([("category".to_string(), json!("Office"))]),
+            )
+        }
+
+        #[test]
+        fn test_scalar_function_in_select_list() {
+            let result = build(FunctionReturn::scalar("bigint"), &call()).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "public"."stats"("category" := $1) AS "stats""#
+            );
+
+            let result = build(FunctionReturn::void(), &call()).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "public"."stats"("category" := $1) AS "stats""#
+            );
+        }
+
+        #[test]
+        fn test_set_of_scalars_aliased_to_function_name() {
+            let params = call()
+                .with_filters(vec![LogicCondition::Filter(
+                    parse_filter("stats", "like.*a*").unwrap(),
+                )])
+                .with_limit(5);
+            let result = build(FunctionReturn::scalar("text").set_of(), &params).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT * FROM "public"."stats"("category" := $1) AS "stats" WHERE "stats" LIKE $2 LIMIT $3"#
+            );
+        }
+
+        #[test]
+        fn test_composite_functions_in_from() {
+            let result = build(FunctionReturn::composite("products"), &call()).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT * FROM "public"."stats"("category" := $1)"#
+            );
+
+            let params = call().with_order(parse_order("price.desc").unwrap());
+            let result = build(FunctionReturn::composite("products").set_of(), &params).unwrap();
+            assert!(result
+                .query
+                .ends_with(r#"("category" := $1) ORDER BY "price" DESC"#));
+        }
+
+        #[test]
+        fn test_filtering_non_set_result_fails() {
+            let filtered = call().with_filters(vec![LogicCondition::Filter(
+                parse_filter("total", "gt.5").unwrap(),
+            )]);
+            for returns in [
+                FunctionReturn::scalar("bigint"),
+                FunctionReturn::composite("orders"),
+                FunctionReturn::void(),
+            ] {
+                assert_eq!(
+                    build(returns.clone(), &filtered).unwrap_err(),
+                    SqlError::FunctionNotSetReturning("stats".to_string())
+                );
+                assert!(build(returns, &call().with_limit(1)).is_err());
+            }
+        }
+
+        #[test]
+        fn test_single_argument_cast_to_parameter_type() {
+            let mut cache = SchemaCache::new();
+            cache.add_function(
+                Function::new("public", "ingest")
+                    .with_params(vec![FunctionParam::new("", "jsonb")])
+                    .returning(FunctionReturn::scalar("integer")),
+            );
+            let params = RpcParams::new("ingest", HashMap::new())
+                .with_body(RpcBody::SingleObject(json!([1, 2])));
+
+            let result = QueryBuilder::new()
+                .with_schema_cache(Arc::new(cache))
+                .build_rpc(&ResolvedTable::new("public", "ingest"), &params)
+                .unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "public"."ingest"($1::jsonb) AS "ingest""#
+            );
+        }
+
+        #[test]
+        fn test_raw_body_needs_parameter_of_its_media_type() {
+            let mut cache = SchemaCache::new();
+            for type_ in ["integer", "jsonb", "text", "bytea"] {
+                cache.add_function(
+                    Function::new("public", "echo")
+                        .with_params(vec![FunctionParam::new("", type_)])
+                        .returning(FunctionReturn::scalar(type_)),
+                );
+            }
+            let cache = Arc::new(cache);
+            let build = |media_type| {
+                let params = RpcParams::new("echo", HashMap::new())
+                    .with_body(RpcBody::Raw(media_type, "hello".to_string()));
+                QueryBuilder::new()
+                    .with_schema_cache(cache.clone())
+                    .build_rpc(&ResolvedTable::new("public", "echo"), &params)
+            };
+
+            let result = build(RawMediaType::Text).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "public"."echo"($1::text) AS "echo""#
+            );
+            let result = build(RawMediaType::Binary).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "public"."echo"($1::bytea) AS "echo""#
+            );
+
+            // A single parameter of another type, or a named one, is no match
+            let mut cache = SchemaCache::new();
+            cache.add_function(
+                Function::new("public", "echo")
+                    .with_params(vec![FunctionParam::new("", "integer")]),
+            );
+            cache.add_function(
+                Function::new("public", "echo")
+                    .with_params(vec![FunctionParam::new("message", "text")]),
+            );
+            let params = RpcParams::new("echo", HashMap::new())
+                .with_body(RpcBody::Raw(RawMediaType::Text, "hello".to_string()));
+            let err = QueryBuilder::new()
+                .with_schema_cache(Arc::new(cache))
+                .build_rpc(&ResolvedTable::new("public", "echo"), &params)
+                .unwrap_err();
+            assert!(matches!(err, SqlError::FunctionNotFound { .. }));
+        }
+
+        #[test]
+        fn test_batch_calls_in_one_statement() {
+            let rows = vec![
+                HashMap::from([("category".to_string(), json!("Office"))]),
+                HashMap::from([("category".to_string(), json!("Kitchen"))]),
+            ];
+            let params = RpcParams::new("stats", HashMap::new())
+                .with_body(RpcBody::Batch(rows))
+                .with_order(parse_order("stats.desc").unwrap());
+
+            let result = build(FunctionReturn::scalar("bigint"), &params).unwrap();
+            assert_eq!(
+                result.query,
+                concat!(
+                    r#"SELECT * FROM (SELECT "stats".* FROM json_to_recordset($1::json) AS "pgrst_args"("category" text), "#,
+                    r#"LATERAL "public"."stats"("category" := "pgrst_args"."category") AS "stats") AS "stats" "#,
+                    r#"ORDER BY "stats" DESC"#
+                )
+            );
+            assert_eq!(
+                result.params,
+                vec![json!([{"category": "Office"}, {"category": "Kitchen"}])]
+            );
+
+            let rows = vec![HashMap::from([("other".to_string(), json!(1))])];
+            let params = RpcParams::new("stats", HashMap::new()).with_body(RpcBody::Batch(rows));
+            assert_eq!(
+                build(FunctionReturn::scalar("bigint"), &params).unwrap_err(),
+                SqlError::FunctionNotFound {
+                    function: "stats".to_string(),
+                    arguments: vec!["other".to_string()],
+                    candidates: vec!["public.stats(category text)".to_string()],
+                }
+            );
+        }
+
+        fn build_overloaded(params: &RpcParams) -> Result<crate::sql::QueryResult, SqlError> {
+            let mut cache = SchemaCache::new();
+            for params in [
+                vec![FunctionParam::new("term", "text")],
+                vec![
+                    FunctionParam::new("term", "text"),
+                    FunctionParam::new("max_results", "integer"),
+                ],
+                vec![
+                    FunctionParam::new("tags", "text[]").as_variadic(),
+                    FunctionParam::new("max_results", "integer").with_default(true),
+                ],
+                vec![FunctionParam::new("tags", "text[]")],
+            ] {
+                cache.add_function(
+                    Function::new("public", "search")
+                        .with_params(params)
+                        .returning(FunctionReturn::composite("products").set_of()),
+                );
+            }
+            QueryBuilder::new()
+                .with_schema_cache(Arc::new(cache))
+                .build_rpc(&ResolvedTable::new("public", "search"), params)
+        }
+
+        fn search(args: &[(&str, Value)]) -> RpcParams {
+            let args = args
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect();
+            RpcParams::new("search", args)
+        }
+
+        #[test]
+        fn test_overload_chosen_by_argument_names() {
+            let result = build_overloaded(&search(&[("term", json!("desk"))])).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT * FROM "public"."search"("term" := $1)"#
+            );
+
+            let params = search(&[("term", json!("desk")), ("max_results", json!(5))]);
+            let result = build_overloaded(&params).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT * FROM "public"."search"("max_results" := $1, "term" := $2)"#
+            );
+        }
+
+        #[test]
+        fn test_variadic_argument_passed_as_array() {
+            let params = search(&[("tags", json!(["a", "b"])), ("max_results", json!(5))]);
+            let result = build_overloaded(&params).unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT * FROM "public"."search"("max_results" := $1, VARIADIC "tags" := $2::text[])"#
+            );
+            assert_eq!(result.params, vec![json!(5), json!(["a", "b"])]);
+
+            // A single value becomes a one-element array
+            let mut cache = SchemaCache::new();
+            cache.add_function(
+                Function::new("public", "tagged")
+                    .with_params(vec![FunctionParam::new("tags", "text[]").as_variadic()]),
+            );
+            let params =
+                RpcParams::new("tagged", HashMap::from([("tags".to_string(), json!("a"))]));
+            let result = QueryBuilder::new()
+                .with_schema_cache(Arc::new(cache))
+                .build_rpc(&ResolvedTable::new("public", "tagged"), &params)
+                .unwrap();
+            assert_eq!(
+                result.query,
+                r#"SELECT "public"."tagged"(VARIADIC "tags" := $1::text[]) AS "tagged""#
+            );
+            assert_eq!(result.params, vec![json!(["a"])]);
+        }
+
+        #[test]
+        fn test_ambiguous_overload_lists_matches() {
+            let params = search(&[("tags", json!(["a"]))]);
+            assert_eq!(
+                build_overloaded(&params).unwrap_err(),
+                SqlError::AmbiguousFunction {
+                    function: "search".to_string(),
+                    candidates: vec![
+                        "public.search(VARIADIC tags text[], max_results integer)".to_string(),
+                        "public.search(tags text[])".to_string(),
+                    ],
+                }
+            );
+        }
+
+        #[test]
+        fn test_missing_overload_lists_candidates() {
+            let params = search(&[("max_results", json!(5))]);
+            let err = build_overloaded(&params).unwrap_err();
+            assert_eq!(
+                err,
+                SqlError::FunctionNotFound {
+                    function: "search".to_string(),
+                    arguments: vec!["max_results".to_string()],
+                    candidates: vec![
+                        "public.search(term text)".to_string(),
+                        "public.search(term text, max_results integer)".to_string(),
+                        "public.search(VARIADIC tags text[], max_results integer)".to_string(),
+                        "public.search(tags text[])".to_string(),
+                    ],
+                }
+            );
+        }
+    }
 }
@@ -42,6 +42,12 @@ pub struct WasmQueryResult {
     params: Vec<serde_json::Value>,
     /// List of tables referenced in the query
     tables: Vec<String>,
+    /// Count queries requested with `Prefer: count=...`
+    count: Option<crate::CountQuery>,
+    /// Body plurality when the query is wrapped into a single response row
+    wrapped: Option<crate::Plurality>,
+    /// Preferences honored by the query, for the `Preference-Applied` header
+    preference_applied: Vec<String>,
 }
 
 #[wasm_bindgen]
@@ -64,6 +70,24 @@ impl WasmQueryResult {
         serde_wasm_bindgen::to_value(&self.tables).unwrap_or(JsValue::NULL)
     }
 
+    /// Get the count queries (`exact`, `planned`, `threshold`, `params`), or null
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.count).unwrap_or(JsValue::NULL)
+    }
+
+    /// Get `"singular"` or `"multiple"` for a wrapped query, or null
+    #[wasm_bindgen(getter)]
+    pub fn wrapped(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.wrapped).unwrap_or(JsValue::NULL)
+    }
+
+    /// Get the honored preferences as a JSON array, e.g. `["return=minimal"]`
+    #[wasm_bindgen(getter, js_name = preferenceApplied)]
+    pub fn preference_applied(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.preference_applied).unwrap_or(JsValue::NULL)
+    }
+
     /// Get the entire result as a JSON object
     #[wasm_bindgen(js_name = toJSON)]
     pub fn to_json(&self) -> JsValue {
@@ -105,6 +129,9 @@ pub fn parse_query_string_wasm(
         query: result.query,
         params: result.params,
         tables: result.tables,
+        count: result.count,
+        wrapped: result.wrapped,
+        preference_applied: result.preference_applied,
     })
 }
 
@@ -345,6 +372,9 @@ pub fn parse_request_wasm(
         query: result.query,
         params: result.params,
         tables: result.tables,
+        count: result.count,
+        wrapped: result.wrapped,
+        preference_applied: result.preference_applied,
     })
 }
 
@@ -354,6 +384,14 @@ pub fn parse_request_wasm(
 /// and returns results. The schema introspection queries will be executed via
 /// this callback to populate the relationship cache.
 ///
+/// No schema cache is built or stored yet: the foreign keys are fetched and
+/// counted, and SQL generation in WASM does not use them. Primary keys and
+/// function metadata are not fetched at all, so embeds, volatility checks for
+/// GET, query-string arguments, return-type shapes, overload resolution and
+/// `VARIADIC` arguments need the native build with the `postgres` feature; in
+/// WASM, `parseRpc` and `parseRequest` read every RPC query key as a filter and
+/// pass the body as named arguments.
+///
 /// # Arguments
 ///
 /// * `query_executor` - An async JavaScript function with signature:
@@ -379,27 +417,7 @@ pub fn parse_request_wasm(
 #[wasm_bindgen(js_name = initSchemaFromDb)]
 pub async fn init_schema_from_db(query_executor: js_sys::Function) -> Result<(), JsValue> {
     // Query for foreign keys from pg_catalog
-    let fk_query = r#"
-        SELECT
-            con.conname AS constraint_name,
-            sn.nspname AS from_schema,
-            sc.relname AS from_table,
-            sa.attname AS from_column,
-            tn.nspname AS to_schema,
-            tc.relname AS to_table,
-            ta.attname AS to_column
-        FROM pg_constraint con
-        JOIN pg_class sc ON sc.oid = con.conrelid
-        JOIN pg_namespace sn ON sn.oid = sc.relnamespace
-        JOIN pg_class tc ON tc.oid = con.confrelid
-        JOIN pg_namespace tn ON tn.oid = tc.relnamespace
-        JOIN pg_attribute sa ON sa.attrelid = sc.oid AND sa.attnum = con.conkey[1]
-        JOIN pg_attribute ta ON ta.attrelid = tc.oid AND ta.attnum = con.confkey[1]
-        WHERE con.contype = 'f'
-          AND sn.nspname NOT IN ('pg_catalog', 'information_schema')
-          AND array_length(con.conkey, 1) = 1
-        ORDER BY sn.nspname, sc.relname, con.conname
-    "#;
+    let fk_query = crate::schema_cache::FOREIGN_KEYS_QUERY;
 
     // Call the JavaScript query executor
     let this = JsValue::null();
@@ -424,10 +442,10 @@ pub async fn init_schema_from_db(query_executor: js_sys::Function) -> Result<(),
         serde_wasm_bindgen::from_value(rows_value)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse foreign keys: {}", e)))?;
 
-    // TODO: Build SchemaCache and store globally for use in SQL generation
-    // For now, just log success
+    // TODO: Build SchemaCache (foreign keys, primary keys and functions) and
+    // store it globally for use in SQL generation
     web_sys::console::log_1(&JsValue::from_str(&format!(
-        "Schema loaded: {} foreign keys",
+        "Schema fetched: {} foreign keys",
         foreign_keys.len()
     )));
 
@@ -89,6 +89,43 @@ CREATE TABLE customer_profiles (
     preferences JSONB DEFAULT '{}'
 );
 
+-- ============================================================================
+-- Composite foreign key example
+-- ============================================================================
+
+-- Tenant-scoped projects (composite primary key)
+CREATE TABLE tenant_projects (
+    tenant_id INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    PRIMARY KEY (tenant_id, id)
+);
+
+-- Tenant-scoped tasks (composite FK to tenant_projects)
+CREATE TABLE tenant_tasks (
+    tenant_id INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    project_id INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    PRIMARY KEY (tenant_id, id),
+    FOREIGN KEY (tenant_id, project_id) REFERENCES tenant_projects(tenant_id, id)
+);
+
+-- ============================================================================
+-- Multiple foreign keys between the same tables
+-- ============================================================================
+
+-- Addresses (referenced twice by orders, embedding needs a hint)
+CREATE TABLE addresses (
+    id SERIAL PRIMARY KEY,
+    street VARCHAR(255) NOT NULL,
+    city VARCHAR(100) NOT NULL
+);
+
+ALTER TABLE orders
+    ADD COLUMN billing_address_id INTEGER REFERENCES addresses(id),
+    ADD COLUMN shipping_address_id INTEGER REFERENCES addresses(id);
+
 -- ============================================================================
 -- Full-text search index
 -- ============================================================================
@@ -108,6 +145,68 @@ CREATE TRIGGER products_search_update
     FOR EACH ROW
     EXECUTE FUNCTION update_product_search_vector();
 
+-- ============================================================================
+-- Functions called through /rpc
+-- ============================================================================
+
+CREATE FUNCTION search_products(term TEXT, max_price NUMERIC DEFAULT NULL)
+RETURNS SETOF products AS $$
+    SELECT * FROM products
+    WHERE name ILIKE '%' || term || '%'
+      AND (max_price IS NULL OR price <= max_price)
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION place_order(customer INTEGER)
+RETURNS orders AS $$
+    INSERT INTO orders (customer_id) VALUES (customer) RETURNING *
+$$ LANGUAGE sql;
+
+CREATE FUNCTION count_products(category_name TEXT)
+RETURNS BIGINT AS $$
+    SELECT count(*) FROM products WHERE category = category_name
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION product_categories()
+RETURNS SETOF TEXT AS $$
+    SELECT DISTINCT category FROM products
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION archive_order(order_id INTEGER)
+RETURNS VOID AS $$
+    UPDATE orders SET status = 'archived' WHERE id = order_id
+$$ LANGUAGE sql;
+
+CREATE FUNCTION sum_quantities(JSONB)
+RETURNS BIGINT AS $$
+    SELECT sum((item->>'quantity')::bigint) FROM jsonb_array_elements($1) AS item
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE FUNCTION describe_customer(payload JSON)
+RETURNS TEXT AS $$
+    SELECT (payload->>'name') || ' <' || (payload->>'email') || '>'
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE FUNCTION shout(TEXT)
+RETURNS TEXT AS $$
+    SELECT upper($1)
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Overloads, told apart by their argument names
+CREATE FUNCTION find_products(term TEXT)
+RETURNS SETOF products AS $$
+    SELECT * FROM products WHERE name ILIKE '%' || term || '%'
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION find_products(term TEXT, category_name TEXT)
+RETURNS SETOF products AS $$
+    SELECT * FROM products WHERE name ILIKE '%' || term || '%' AND category = category_name
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION products_by_ids(VARIADIC ids INTEGER[])
+RETURNS SETOF products AS $$
+    SELECT * FROM products WHERE id = ANY(ids)
+$$ LANGUAGE sql STABLE;
+
 -- ============================================================================
 -- Seed Data
 -- ============================================================================
@@ -186,6 +285,24 @@ INSERT INTO post_tags (post_id, tag_id) VALUES
 (3, 1), (3, 2),
 (5, 1), (5, 4);
 
+-- Addresses (billing and shipping for the first orders)
+INSERT INTO addresses (street, city) VALUES
+('1 Main St', 'Springfield'),
+('42 Harbor Rd', 'Shelbyville');
+
+UPDATE orders SET billing_address_id = 1, shipping_address_id = 2 WHERE id IN (1, 2);
+
+-- Tenant projects and tasks (composite keys)
+INSERT INTO tenant_projects (tenant_id, id, name) VALUES
+(1, 1, 'Website Redesign'),
+(1, 2, 'Mobile App'),
+(2, 1, 'Data Migration');
+
+INSERT INTO tenant_tasks (tenant_id, id, project_id, title) VALUES
+(1, 1, 1, 'Design mockups'),
+(1, 2, 2, 'Build prototype'),
+(2, 1, 1, 'Export legacy data');
+
 -- ============================================================================
 -- Helpful comments for understanding relationships
 -- ============================================================================
@@ -198,3 +315,5 @@ COMMENT ON TABLE posts IS 'Blog posts - M2O to customers (author), M2M to tags';
 COMMENT ON TABLE tags IS 'Post tags - M2M to posts through post_tags';
 COMMENT ON TABLE post_tags IS 'Junction table for posts-tags M2M relationship';
 COMMENT ON TABLE customer_profiles IS 'O2O extension of customers table';
+COMMENT ON TABLE tenant_tasks IS 'Tenant-scoped tasks - M2O to tenant_projects via composite FK';
+COMMENT ON TABLE addresses IS 'Postal addresses - referenced by orders as billing and shipping address';
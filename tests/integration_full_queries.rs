@@ -5,7 +5,10 @@
 
 #![cfg(feature = "postgres")]
 
-use postgrest_parser::{parse_query_string, QueryBuilder, SchemaCache};
+use postgrest_parser::{
+    parse_query_string, Count, CountQuery, Field, JsonOp, Plurality, QueryBuilder, SchemaCache,
+    SelectItem, SqlError,
+};
 use sqlx::postgres::PgPoolOptions;
 use std::sync::Arc;
 
@@ -44,6 +47,286 @@ async fn test_customers_with_filters_and_ordering() {
     println!("Found {} gold tier customers", rows.len());
 }
 
+#[tokio::test]
+async fn test_select_json_paths_and_casts() {
+    let pool = get_pool().await;
+
+    // Query: JSON paths and casts in the select list, named like PostgREST
+    let params = parse_query_string(
+        "select=id,metadata->>tier,newsletter:metadata->preferences->newsletter,id_text:id::text&id=eq.1",
+    )
+    .unwrap();
+
+    let result = QueryBuilder::new()
+        .build_select("customers", &params)
+        .unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    let row: String = sqlx::query_scalar(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .bind(result.params[0].as_str().unwrap().parse::<i32>().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let row: serde_json::Value = serde_json::from_str(&row).unwrap();
+
+    assert_eq!(row["tier"], "gold");
+    assert_eq!(row["newsletter"], true);
+    assert_eq!(row["id_text"], "1");
+}
+
+#[tokio::test]
+async fn test_aggregates_with_implicit_group_by() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // Query: Order totals per status, grouped by the plain status column
+    let params =
+        parse_query_string("select=status,total_amount.sum(),count()&order=status.asc").unwrap();
+    let result = QueryBuilder::new().build_select("orders", &params).unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    let rows: Vec<String> = sqlx::query_scalar(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let rows: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| serde_json::from_str(row).unwrap())
+        .collect();
+
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[1]["status"], "completed");
+    assert_eq!(rows[1]["count"], 3);
+    assert_eq!(rows[1]["sum"], 2909.94);
+
+    // Aggregates inside an embedded resource group within the subquery
+    let params =
+        parse_query_string("select=name,orders(status,count())&orders.order=status&id=eq.1")
+            .unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .with_schema("public")
+        .build_select("customers", &params)
+        .unwrap();
+
+    let row: String = sqlx::query_scalar(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .bind(result.params[0].as_str().unwrap().parse::<i32>().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let row: serde_json::Value = serde_json::from_str(&row).unwrap();
+
+    assert_eq!(
+        row["orders"],
+        serde_json::json!([
+            {"status": "completed", "count": 1},
+            {"status": "pending", "count": 1}
+        ])
+    );
+
+    // Aggregates next to a spread group by the spread columns, and next to an
+    // embed by the columns it is correlated on
+    let rows_of = |query: &str| {
+        let params = parse_query_string(query).unwrap();
+        let result = QueryBuilder::new()
+            .with_schema_cache(cache.clone())
+            .with_schema("public")
+            .build_select("orders", &params)
+            .unwrap();
+        println!("Generated SQL:\n{}\n", result.query);
+        format!("SELECT row_to_json(t)::text FROM ({}) t", result.query)
+    };
+
+    let mut rows: Vec<String> = sqlx::query_scalar(&rows_of("select=count(),...customers(name)"))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            r#"{"count":1,"name":"Charlie Brown"}"#,
+            r#"{"count":2,"name":"Alice Johnson"}"#,
+            r#"{"count":2,"name":"Bob Smith"}"#,
+            r#"{"count":2,"name":"Diana Prince"}"#,
+        ]
+    );
+
+    let rows: Vec<String> = sqlx::query_scalar(&rows_of(
+        "select=status,count(),customers(name)&status=eq.completed",
+    ))
+    .bind("completed")
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(rows.len(), 3);
+}
+
+#[tokio::test]
+async fn test_quotes_in_json_keys_and_aliases_are_escaped() {
+    let pool = get_pool().await;
+
+    // Query: JSON keys and aliases containing quotes stay inside their quotes
+    let mut params = parse_query_string("select=id&metadata->>tier=eq.gold&order=id.asc").unwrap();
+    params.select.as_mut().unwrap().push(
+        SelectItem::field("metadata").with_alias("x\"y").with_field(
+            Field::new("metadata").with_json_path(vec![JsonOp::DoubleArrow("it's".to_string())]),
+        ),
+    );
+    let result = QueryBuilder::new()
+        .build_select("customers", &params)
+        .unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    let rows: Vec<String> = sqlx::query_scalar(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .bind(result.params[0].as_str().unwrap())
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let rows: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| serde_json::from_str(row).unwrap())
+        .collect();
+
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0]["x\"y"].is_null());
+}
+
+#[tokio::test]
+async fn test_prefer_count_queries() {
+    use sqlx::Row;
+
+    let pool = get_pool().await;
+
+    // Query: First page of electronics, with the total for Content-Range
+    let params =
+        parse_query_string("category=eq.Electronics&order=price.desc&limit=2&offset=1").unwrap();
+    let result = QueryBuilder::new()
+        .with_count(Count::Estimated)
+        .build_select("products", &params)
+        .unwrap();
+    let count = result.count.unwrap();
+
+    println!("Count SQL:\n{}\n", count.exact.as_deref().unwrap());
+
+    let exact: i64 = sqlx::query_scalar(count.exact.as_deref().unwrap())
+        .bind(count.params[0].as_str().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(exact, 4);
+
+    // EXPLAIN returns json, read as text to avoid the sqlx json feature
+    let row = sqlx::query(count.planned.as_deref().unwrap())
+        .bind(count.params[0].as_str().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let plan: serde_json::Value =
+        serde_json::from_str(&row.try_get_unchecked::<String, _>(0).unwrap()).unwrap();
+    let planned = CountQuery::planned_rows(&plan).unwrap();
+    assert!(planned > 0);
+    assert!(
+        count.needs_exact(planned),
+        "small tables use the exact count"
+    );
+}
+
+#[tokio::test]
+async fn test_wrapped_json_response() {
+    use sqlx::Row;
+
+    let pool = get_pool().await;
+
+    // Query: Second page of electronics as one JSON body, with the total
+    let params =
+        parse_query_string("select=name&category=eq.Electronics&order=price.desc&limit=2&offset=1")
+            .unwrap();
+    let result = QueryBuilder::new()
+        .with_count(Count::Exact)
+        .with_wrapped_response(Plurality::Multiple)
+        .build_select("products", &params)
+        .unwrap();
+
+    println!("Wrapped SQL:\n{}\n", result.query);
+
+    let row = sqlx::query(&result.query)
+        .bind(result.params[0].as_str().unwrap())
+        .bind(result.params[1].as_i64().unwrap())
+        .bind(result.params[2].as_i64().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let total: Option<i64> = row.get("total_result_set");
+    let page_total: i64 = row.get("page_total");
+    let body: serde_json::Value =
+        serde_json::from_str(&row.try_get_unchecked::<String, _>("body").unwrap()).unwrap();
+    assert_eq!(total, Some(4));
+    assert_eq!(page_total, 2);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert!(body[0].get("name").is_some());
+    assert!(result.check_page_total(page_total).is_ok());
+
+    // Query: A single object, which must match exactly one row
+    let singular = |query: &str| {
+        QueryBuilder::new()
+            .with_wrapped_response(Plurality::Singular)
+            .build_select("products", &parse_query_string(query).unwrap())
+            .unwrap()
+    };
+
+    let result = singular("select=name,price&name=eq.Laptop Pro");
+    let row = sqlx::query(&result.query)
+        .bind(result.params[0].as_str().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let body: serde_json::Value =
+        serde_json::from_str(&row.try_get_unchecked::<String, _>("body").unwrap()).unwrap();
+    assert_eq!(body["name"], "Laptop Pro");
+    assert!(result.check_page_total(row.get("page_total")).is_ok());
+
+    let result = singular("category=eq.Electronics");
+    let row = sqlx::query(&result.query)
+        .bind(result.params[0].as_str().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        result.check_page_total(row.get("page_total")),
+        Err(SqlError::SingularRowCount(4))
+    );
+
+    // No rows still returns the response row, with a null body
+    let result = singular("name=eq.Nothing");
+    let row = sqlx::query(&result.query)
+        .bind(result.params[0].as_str().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(row.try_get_unchecked::<String, _>("body").unwrap(), "null");
+    assert_eq!(
+        result.check_page_total(row.get("page_total")),
+        Err(SqlError::SingularRowCount(0))
+    );
+}
+
 #[tokio::test]
 async fn test_orders_with_customer_details() {
     let pool = get_pool().await;
@@ -324,8 +607,9 @@ async fn test_junction_table_many_to_many() {
     let pool = get_pool().await;
     let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
 
-    // Query: Get posts (should work even though we don't fully support M2M yet)
-    let params = parse_query_string("select=id,title,published&published=eq.true").unwrap();
+    // Query: Get published posts with their tags through the post_tags junction
+    let params =
+        parse_query_string("select=id,title,published,tags(name)&published=eq.true").unwrap();
 
     let mut builder = QueryBuilder::new()
         .with_schema_cache(cache)
@@ -336,6 +620,11 @@ async fn test_junction_table_many_to_many() {
     println!("Posts query SQL:\n{}\n", result.query);
     println!("Params: {:?}", result.params);
 
+    assert!(result.query.contains(r#"INNER JOIN "public"."post_tags""#));
+    assert!(result
+        .query
+        .contains(r#""post_tags"."post_id" = "posts"."id""#));
+
     // Execute query - handle boolean parameter
     let published_value = if result.params[0].is_boolean() {
         result.params[0].as_bool().unwrap()
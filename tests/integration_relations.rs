@@ -7,7 +7,7 @@
 
 #![cfg(feature = "postgres")]
 
-use postgrest_parser::{parse_query_string, QueryBuilder, SchemaCache};
+use postgrest_parser::{parse_query_string, QueryBuilder, RelationType, SchemaCache, SqlError};
 use sqlx::postgres::PgPoolOptions;
 use std::sync::Arc;
 
@@ -96,16 +96,412 @@ async fn test_nested_relations() {
         .with_schema_cache(cache.clone())
         .with_schema("public");
 
-    let result = builder.build_select("customers", &params);
+    let result = builder.build_select("customers", &params).unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    // Every level is correlated with its parent
+    assert!(result
+        .query
+        .contains(r#""orders"."customer_id" = "customers"."id""#));
+    assert!(result
+        .query
+        .contains(r#""order_items"."order_id" = "orders"."id""#));
+    assert!(result
+        .query
+        .contains(r#""order_items"."product_id" = "products"."id""#));
+
+    // Execute and check the nested shape
+    let rows: Vec<serde_json::Value> = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .fetch_all(&pool)
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| serde_json::from_str(row).unwrap())
+    .collect();
+    let item = rows
+        .iter()
+        .flat_map(|row| row["orders"].as_array().unwrap())
+        .flat_map(|order| order["order_items"].as_array().unwrap())
+        .next()
+        .expect("fixtures contain at least one order item");
+    assert!(item["products"]["name"].is_string());
+}
+
+#[tokio::test]
+async fn test_same_table_embedded_twice() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // customers -> orders -> customers again needs distinct aliases
+    let params = parse_query_string("select=id,orders(id,customers(name))").unwrap();
+
+    let mut builder = QueryBuilder::new()
+        .with_schema_cache(cache)
+        .with_schema("public");
+
+    let result = builder.build_select("customers", &params).unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+    assert!(result.query.contains(r#""customers_2""#));
+
+    let rows = sqlx::query(&result.query).fetch_all(&pool).await.unwrap();
+    assert!(!rows.is_empty());
+}
+
+#[tokio::test]
+async fn test_embedded_filters_order_and_limit() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // Filters, ordering and limits scoped to the embed never drop parent rows
+    let params = parse_query_string(
+        "select=id,orders(id,status)&orders.status=eq.completed&orders.order=id.desc&orders.limit=1&order=id.asc",
+    )
+    .unwrap();
+
+    let mut builder = QueryBuilder::new()
+        .with_schema_cache(cache)
+        .with_schema("public");
+
+    let result = builder.build_select("customers", &params).unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+    assert!(result.query.contains(
+        r#""orders"."customer_id" = "customers"."id" AND "orders"."status" = $1 ORDER BY "orders"."id" DESC LIMIT $2"#
+    ));
+
+    let rows: Vec<serde_json::Value> = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .bind(result.params[0].as_str().unwrap())
+    .bind(result.params[1].as_i64().unwrap())
+    .fetch_all(&pool)
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| serde_json::from_str(row).unwrap())
+    .collect();
+
+    assert_eq!(rows.len(), 5, "Every customer is returned");
+    for row in &rows {
+        let orders = row["orders"].as_array().unwrap();
+        assert!(orders.len() <= 1);
+        assert!(orders.iter().all(|order| order["status"] == "completed"));
+    }
+    assert!(rows[2]["orders"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_inner_join_and_null_embed_filters() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // !inner keeps only customers with a cancelled order
+    let params = parse_query_string(
+        "select=id,orders!inner(id,status)&orders.status=eq.cancelled&order=id.asc",
+    )
+    .unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .with_schema("public")
+        .build_select("customers", &params)
+        .unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+    assert!(result.query.contains("WHERE EXISTS (SELECT 1 FROM"));
+
+    let ids: Vec<i32> = sqlx::query_scalar(&format!("SELECT id FROM ({}) t", result.query))
+        .bind(result.params[0].as_str().unwrap())
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(ids, vec![2]);
+
+    // orders=is.null keeps only customers without orders (anti-join)
+    let params = parse_query_string("select=id,orders(id)&orders=is.null").unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .with_schema("public")
+        .build_select("customers", &params)
+        .unwrap();
+    assert!(result.query.contains("WHERE NOT EXISTS (SELECT 1 FROM"));
+
+    let without_orders: Vec<i32> =
+        sqlx::query_scalar(&format!("SELECT id FROM ({}) t", result.query))
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+    // orders=not.is.null is the matching semi-join
+    let params = parse_query_string("select=id,orders(id)&orders=not.is.null").unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache)
+        .with_schema("public")
+        .build_select("customers", &params)
+        .unwrap();
+    let with_orders: Vec<i32> = sqlx::query_scalar(&format!("SELECT id FROM ({}) t", result.query))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(without_orders, vec![5]);
+    assert_eq!(with_orders.len(), 4);
+    assert!(!with_orders.contains(&5));
+}
+
+#[tokio::test]
+async fn test_relationship_disambiguation_hints() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // orders references addresses twice: embedding without a hint is ambiguous
+    let params = parse_query_string("select=id,addresses(*)").unwrap();
+    let err = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .build_select("orders", &params)
+        .unwrap_err();
+    match err {
+        SqlError::AmbiguousRelationship { candidates, .. } => assert_eq!(
+            candidates,
+            vec![
+                "addresses!orders_billing_address_id_fkey",
+                "addresses!orders_shipping_address_id_fkey"
+            ]
+        ),
+        other => panic!("Expected ambiguity error, got {:?}", other),
+    }
+
+    // A constraint name or a column picks one relationship each
+    let params = parse_query_string(
+        "select=id,billing:addresses!orders_billing_address_id_fkey(city),shipping:addresses!shipping_address_id(city)&id=eq.1",
+    )
+    .unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache)
+        .with_schema("public")
+        .build_select("orders", &params)
+        .unwrap();
 
-    // This should work for first level
-    if let Ok(result) = result {
-        println!("Generated SQL:\n{}\n", result.query);
-        assert!(result.query.contains("orders"));
-    } else {
-        // Nested relations might not be fully supported yet
-        println!("Nested relations not yet fully supported");
+    println!("Generated SQL:\n{}\n", result.query);
+
+    let row: String = sqlx::query_scalar(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .bind(result.params[0].as_str().unwrap().parse::<i32>().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let row: serde_json::Value = serde_json::from_str(&row).unwrap();
+    assert_eq!(row["billing"]["city"], "Springfield");
+    assert_eq!(row["shipping"]["city"], "Shelbyville");
+}
+
+#[tokio::test]
+async fn test_spread_relation_flattens_columns() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    let params = parse_query_string(
+        "select=id,...customers(customer_name:name),...addresses!billing_address_id(city)&order=id.asc",
+    )
+    .unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .with_schema("public")
+        .build_select("orders", &params)
+        .unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+    assert!(result.query.contains("LEFT JOIN LATERAL"));
+
+    let rows: Vec<serde_json::Value> = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .fetch_all(&pool)
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| serde_json::from_str(row).unwrap())
+    .collect();
+
+    assert_eq!(rows.len(), 7, "Spreading never drops parent rows");
+    assert_eq!(rows[0]["customer_name"], "Alice Johnson");
+    assert_eq!(rows[0]["city"], "Springfield");
+    assert!(rows[6]["city"].is_null());
+
+    // Spreading a to-many relationship is rejected, also with columns next to
+    // aggregates, which would give one parent row per group
+    for query in [
+        "select=id,...orders(status)",
+        "select=id,...orders(status,n:id.count())",
+    ] {
+        let params = parse_query_string(query).unwrap();
+        let err = QueryBuilder::new()
+            .with_schema_cache(cache.clone())
+            .build_select("customers", &params)
+            .unwrap_err();
+        assert_eq!(err, SqlError::SpreadToMany("orders".to_string()));
     }
+
+    // Unless it only selects aggregates, computed per parent row
+    let params =
+        parse_query_string("select=id,...orders(spent:total_amount.sum())&order=id.asc").unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache)
+        .build_select("customers", &params)
+        .unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    let rows: Vec<serde_json::Value> = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .fetch_all(&pool)
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| serde_json::from_str(row).unwrap())
+    .collect();
+
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[1]["spent"], serde_json::json!(129.98));
+    assert!(rows[4]["spent"].is_null());
+}
+
+#[tokio::test]
+async fn test_order_by_related_to_one_column() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // Orders sorted by their customer's name, then by id
+    let params =
+        parse_query_string("select=id,customers(name)&order=customers(name).desc.nullslast,id")
+            .unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .with_schema("public")
+        .build_select("orders", &params)
+        .unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    let ids: Vec<i32> = sqlx::query_scalar(&format!("SELECT id FROM ({}) t", result.query))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(ids, vec![6, 7, 5, 3, 4, 1, 2]);
+
+    // Related ordering inside an embedded resource
+    let params = parse_query_string(
+        "select=id,order_items(products(name))&order_items.order=products(name).desc&id=eq.1",
+    )
+    .unwrap();
+    let result = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .with_schema("public")
+        .build_select("orders", &params)
+        .unwrap();
+
+    let row: String = sqlx::query_scalar(&format!(
+        "SELECT row_to_json(t)::text FROM ({}) t",
+        result.query
+    ))
+    .bind(result.params[0].as_str().unwrap().parse::<i32>().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let row: serde_json::Value = serde_json::from_str(&row).unwrap();
+    assert_eq!(row["order_items"][0]["products"]["name"], "Wireless Mouse");
+    assert_eq!(row["order_items"][1]["products"]["name"], "Laptop Pro");
+
+    // To-many relationships have no single value to order by
+    let params = parse_query_string("order=orders(total_amount)").unwrap();
+    let err = QueryBuilder::new()
+        .with_schema_cache(cache)
+        .build_select("customers", &params)
+        .unwrap_err();
+    assert_eq!(err, SqlError::OrderByToMany("orders".to_string()));
+}
+
+#[tokio::test]
+async fn test_many_to_many_relation() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // Query: orders with their products through the order_items junction
+    let params = parse_query_string("select=*,products(*)").unwrap();
+
+    let mut builder = QueryBuilder::new()
+        .with_schema_cache(cache.clone())
+        .with_schema("public");
+
+    let result = builder.build_select("orders", &params).unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    assert!(result
+        .query
+        .contains(r#""order_items"."product_id" = "products"."id""#));
+    assert!(result
+        .query
+        .contains(r#""order_items"."order_id" = "orders"."id""#));
+
+    let rows = sqlx::query(&result.query).fetch_all(&pool).await.unwrap();
+    assert!(!rows.is_empty(), "Should return orders with products");
+
+    let rel = cache
+        .find_relationship("public", "orders", "products")
+        .unwrap();
+    assert!(matches!(
+        rel.relation_type,
+        RelationType::ManyToMany { ref junction_table, .. } if junction_table == "order_items"
+    ));
+}
+
+#[tokio::test]
+async fn test_composite_foreign_key_relation() {
+    let pool = get_pool().await;
+    let cache = Arc::new(SchemaCache::load_from_database(&pool).await.unwrap());
+
+    // The FK columns are loaded in constraint order
+    let fks = cache.get_foreign_keys("public", "tenant_tasks");
+    assert_eq!(fks.len(), 1);
+    assert_eq!(fks[0].from_columns, vec!["tenant_id", "project_id"]);
+    assert_eq!(fks[0].to_columns, vec!["tenant_id", "id"]);
+
+    // Query: tasks with their tenant-scoped project
+    let params = parse_query_string("select=title,tenant_projects(name)&order=title.asc").unwrap();
+
+    let mut builder = QueryBuilder::new()
+        .with_schema_cache(cache)
+        .with_schema("public");
+
+    let result = builder.build_select("tenant_tasks", &params).unwrap();
+
+    println!("Generated SQL:\n{}\n", result.query);
+
+    let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT title, tenant_projects->>'name' FROM ({}) t",
+        result.query
+    ))
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    // Task 1 of tenant 2 must join project 1 of tenant 2, not tenant 1
+    assert!(rows.contains(&(
+        "Export legacy data".to_string(),
+        "Data Migration".to_string()
+    )));
+    assert!(rows.contains(&("Design mockups".to_string(), "Website Redesign".to_string())));
 }
 
 #[tokio::test]
@@ -162,8 +558,8 @@ async fn test_schema_cache_foreign_keys() {
     assert!(customer_fk.is_some(), "orders should have FK to customers");
 
     let fk = customer_fk.unwrap();
-    assert_eq!(fk.from_column, "customer_id");
-    assert_eq!(fk.to_column, "id");
+    assert_eq!(fk.from_columns, vec!["customer_id"]);
+    assert_eq!(fk.to_columns, vec!["id"]);
 }
 
 #[tokio::test]
@@ -0,0 +1,244 @@
+//! Property tests for the SQL fragment layer.
+//!
+//! Identifiers, JSON keys, text search configurations and type names are the only
+//! user input written into the query text. These tests generate hostile strings for
+//! each of them and check, with a small PostgreSQL lexer, that the generated query
+//! has exactly the same token structure as the query built from benign names: no
+//! input can close its quotes or add SQL of its own.
+
+use postgrest_parser::sql::fragment::{Ident, Literal, TypeName};
+use postgrest_parser::{
+    Field, Filter, FilterOperator, FilterValue, InsertParams, InsertValues, JsonOp, LogicCondition,
+    OnConflict, OrderTerm, ParsedParams, QueryBuilder, ResolvedTable, RpcParams, SelectItem,
+    SqlError, UpdateParams,
+};
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(String),
+}
+
+/// Splits `sql` into its unquoted text, with every quoted identifier replaced by
+/// `"I"` and every string constant by `'L'`, and the unescaped quoted tokens.
+fn lex(sql: &str) -> Result<(String, Vec<Token>), String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut skeleton = String::new();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                let mut content = String::new();
+                i += 1;
+                loop {
+                    match (chars.get(i), chars.get(i + 1)) {
+                        (Some('"'), Some('"')) => {
+                            content.push('"');
+                            i += 2;
+                        }
+                        (Some('"'), _) => break,
+                        (Some(c), _) => {
+                            content.push(*c);
+                            i += 1;
+                        }
+                        (None, _) => return Err(format!("unterminated identifier in {sql}")),
+                    }
+                }
+                skeleton.push_str("\"I\"");
+                tokens.push(Token::Ident(content));
+            }
+            '\'' => {
+                let escape_string = skeleton.ends_with(['E', 'e'])
+                    && !skeleton[..skeleton.len() - 1]
+                        .ends_with(|c: char| c.is_alphanumeric() || c == '_');
+                let mut content = String::new();
+                i += 1;
+                loop {
+                    match (chars.get(i), chars.get(i + 1)) {
+                        (Some('\\'), Some(c)) if escape_string => {
+                            content.push(*c);
+                            i += 2;
+                        }
+                        (Some('\''), Some('\'')) => {
+                            content.push('\'');
+                            i += 2;
+                        }
+                        (Some('\''), _) => break,
+                        (Some(c), _) => {
+                            content.push(*c);
+                            i += 1;
+                        }
+                        (None, _) => return Err(format!("unterminated string in {sql}")),
+                    }
+                }
+                skeleton.push_str("'L'");
+                tokens.push(Token::Literal(content));
+            }
+            c => skeleton.push(c),
+        }
+        i += 1;
+    }
+
+    Ok((skeleton, tokens))
+}
+
+/// The token structure of `sql`, treating escape string constants like plain ones
+fn skeleton(sql: &str) -> String {
+    lex(sql).unwrap().0.replace("E'L'", "'L'")
+}
+
+/// Arbitrary strings, biased towards the characters that matter for SQL
+fn hostile() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<String>(),
+        "[a-z\"'\\\\;()\\-/* .,:=$]{0,16}",
+        Just(r#"x" OR 1=1 --"#.to_string()),
+        Just("x') OR ('1'='1".to_string()),
+        Just(r"x\'; DROP TABLE users; --".to_string()),
+    ]
+}
+
+/// Hostile table and column names; `*` is the wildcard rather than a name
+fn name() -> impl Strategy<Value = String> {
+    hostile().prop_filter("a table or column name", |name| {
+        !name.is_empty() && name != "*"
+    })
+}
+
+fn build_select(table: &str, column: &str, alias: &str, key: &str, language: &str) -> String {
+    let filter = Filter::new(
+        Field::new(column).with_json_path(vec![
+            JsonOp::Arrow(key.to_string()),
+            JsonOp::DoubleArrow(key.to_string()),
+        ]),
+        FilterOperator::Fts,
+        FilterValue::Single("value".to_string()),
+    )
+    .with_language(language);
+    let params = ParsedParams::new()
+        .with_select(vec![
+            SelectItem::field(column).with_alias(alias),
+            SelectItem::field(column).with_field(
+                Field::new(column).with_json_path(vec![JsonOp::DoubleArrow(key.to_string())]),
+            ),
+        ])
+        .with_filters(vec![LogicCondition::Filter(filter)])
+        .with_order(vec![OrderTerm::new(Field::new(column))]);
+
+    QueryBuilder::new()
+        .build_select(table, &params)
+        .unwrap()
+        .query
+}
+
+fn build_mutations(schema: &str, table: &str, column: &str, alias: &str) -> Vec<String> {
+    let resolved = ResolvedTable::new(schema, table);
+    let row = HashMap::from([(column.to_string(), serde_json::json!("value"))]);
+    let returning = vec![SelectItem::field(column).with_alias(alias)];
+    let filter = LogicCondition::Filter(Filter::new(
+        Field::new(column),
+        FilterOperator::Eq,
+        FilterValue::Single("1".to_string()),
+    ));
+
+    let insert = InsertParams::new(InsertValues::Single(row.clone()))
+        .with_on_conflict(OnConflict::do_update(vec![column.to_string()]))
+        .with_returning(returning.clone());
+    let update = UpdateParams::new(row.clone())
+        .with_filters(vec![filter])
+        .with_returning(returning);
+    let rpc = RpcParams::new(table, row);
+
+    vec![
+        QueryBuilder::new()
+            .build_insert(&resolved, &insert)
+            .unwrap()
+            .query,
+        QueryBuilder::new()
+            .build_update(&resolved, &update)
+            .unwrap()
+            .query,
+        QueryBuilder::new()
+            .build_rpc(&resolved, &rpc)
+            .unwrap()
+            .query,
+    ]
+}
+
+proptest! {
+    #[test]
+    fn ident_is_one_quoted_identifier(name in hostile()) {
+        let (skeleton, tokens) = lex(&Ident(&name).to_string()).unwrap();
+        prop_assert_eq!(skeleton, "\"I\"");
+        prop_assert_eq!(tokens, vec![Token::Ident(name)]);
+    }
+
+    #[test]
+    fn literal_is_one_string_constant(text in hostile()) {
+        let sql = Literal(&text).to_string();
+        let (skeleton, tokens) = lex(&sql).unwrap();
+        prop_assert!(skeleton == "'L'" || skeleton == "E'L'", "{}", sql);
+        prop_assert_eq!(tokens, vec![Token::Literal(text)]);
+    }
+
+    #[test]
+    fn type_name_accepts_only_type_syntax(name in hostile()) {
+        if let Ok(type_name) = TypeName::parse(&name) {
+            prop_assert_eq!(type_name.to_string(), name.clone());
+            prop_assert!(name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || " _.,()[]".contains(c)));
+        }
+    }
+
+    #[test]
+    fn select_names_cannot_escape(
+        table in name(),
+        column in name(),
+        alias in hostile(),
+        key in hostile(),
+        language in hostile(),
+    ) {
+        let sql = build_select(&table, &column, &alias, &key, &language);
+        let benign = build_select("t", "c", "a", "k", "english");
+        prop_assert_eq!(skeleton(&sql), skeleton(&benign));
+    }
+
+    #[test]
+    fn casts_are_validated(cast in hostile()) {
+        let params = ParsedParams::new().with_filters(vec![LogicCondition::Filter(Filter::new(
+            Field::new("c").with_cast(cast.clone()),
+            FilterOperator::Eq,
+            FilterValue::Single("1".to_string()),
+        ))]);
+
+        match QueryBuilder::new().build_select("t", &params) {
+            Ok(result) => {
+                prop_assert!(TypeName::parse(&cast).is_ok());
+                prop_assert_eq!(
+                    skeleton(&result.query),
+                    format!("SELECT * FROM \"I\" WHERE \"I\"::{} = $1", cast)
+                );
+            }
+            Err(err) => prop_assert_eq!(err, SqlError::InvalidTypeCastForSql),
+        }
+    }
+
+    #[test]
+    fn mutation_names_cannot_escape(
+        schema in hostile(),
+        table in name(),
+        column in name(),
+        alias in hostile(),
+    ) {
+        let sql = build_mutations(&schema, &table, &column, &alias);
+        let benign = build_mutations("s", "t", "c", "a");
+        for (sql, benign) in sql.iter().zip(&benign) {
+            prop_assert_eq!(skeleton(sql), skeleton(benign));
+        }
+    }
+}